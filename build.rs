@@ -1,7 +1,5 @@
 #[cfg(not(windows))]
-fn main() {
-    panic!("This software is only compatible with Windows.");
-}
+fn main() {}
 
 #[cfg(windows)]
 fn main() {
@@ -0,0 +1,636 @@
+use log::{error, info, warn};
+
+use crate::{
+    install, logger, platform, power, process, report, shutdown, standby, timer, TimersetResult,
+};
+
+/// TimerSet allows you to change your NT Kernel system timer
+/// Also allows you to monitor Windows Standby List and clean it up when needed
+#[derive(Debug, clap::Parser)]
+#[clap(about, version, author)]
+pub struct Opts {
+    /// Shows the actions taken but do not modify anything on the system; Also known as a dry run.
+    #[clap(short, long)]
+    pub(crate) pretend: bool,
+
+    /// Installs TimerSet to your system and runs it on startup
+    #[clap(short, long)]
+    pub(crate) install: bool,
+
+    /// Uninstalls TimerSet from your system
+    #[clap(short, long)]
+    pub(crate) uninstall: bool,
+
+    /// With `--install`, enables the GlobalTimerResolutionRequests registry value on Windows 11 and Server 2022,
+    /// without which the installed task's timer requests are ignored. Takes effect after a reboot, and gets reverted on uninstall.
+    #[clap(long = "global-timer-requests", requires = "install")]
+    pub(crate) global_timer_requests: bool,
+
+    /// Allows to set a custom timer value. Will be clamped between the bounds of allowed timer values.
+    /// Accepts a unit suffix: `ms`, `us`/`μs` or `hns` (100ns, the kernel unit). Values without a unit are in μs.
+    /// Also note that sometimes, setting high timer values are rejected by the system and will be lowered down depending
+    /// on which clock source your system is using (TSC tends to lower values by ~5μs, HPET does not for instance)
+    #[clap(short, long)]
+    pub(crate) timer: Option<timer::TimerResolution>,
+
+    /// With `--timer`, probes the timer values the system actually grants (see the `probe` command)
+    /// and snaps the value to the closest one that isn't finer. Does nothing on Linux, where any value is granted.
+    #[clap(long, requires = "timer")]
+    pub(crate) snap: bool,
+
+    /// Holds a timer value only while a matching process runs, e.g. `game.exe,*-Win64-Shipping.exe=0.5ms`.
+    /// Can be given several times, in which case the finest value among running processes is held.
+    /// When none of them run, the `--timer` value is held if given, otherwise the timer request is released.
+    #[clap(
+        long = "timer-profile",
+        value_name = "PATTERNS=TIMER",
+        multiple_occurrences = true,
+        conflicts_with = "timer-watchdog"
+    )]
+    pub(crate) timer_profiles: Vec<timer::TimerProfile>,
+
+    /// Timer value held while running on battery, or `release` to withdraw the timer request on battery.
+    /// Takes over `--timer` and `--timer-profile` until back on AC power.
+    #[clap(
+        long = "battery-timer",
+        value_name = "TIMER",
+        conflicts_with = "timer-watchdog"
+    )]
+    pub(crate) battery_timer: Option<timer::BatteryTimer>,
+
+    /// Enables the timer watchdog, which re-checks the timer resolution every N seconds.
+    /// Changes are logged, and the requested timer value gets re-applied whenever something else made it coarser.
+    #[clap(long = "watchdog", value_name = "SECONDS")]
+    pub(crate) timer_watchdog: Option<std::num::NonZeroU64>,
+
+    /// Enables Windows Standby List periodic cleaning.
+    /// It is akin to how ISLC by Wagnard works. On Linux, the page cache is cleaned instead.
+    #[clap(long = "islc")]
+    pub(crate) clean_standby_list: bool,
+
+    /// How the Standby List cleaner checks memory: `notification` waits on LowMemoryResourceNotifications
+    /// (memory pressure triggers on Linux), `poll` samples the memory lists every `--islc-timer` seconds
+    /// and never touches the registry.
+    #[clap(long = "islc-mode", default_value = "notification")]
+    pub(crate) clean_standby_list_mode: standby::CleanerMode,
+
+    /// Appends a sample of the memory lists to FILE each time the Standby List cleaner wakes up and after each purge.
+    /// Files ending in `.csv` are written as CSV, anything else as JSON Lines; see the README for the columns.
+    #[clap(long = "record", value_name = "FILE")]
+    pub(crate) record: Option<std::path::PathBuf>,
+
+    /// Standby List anti-kernel DOS throttle timer
+    /// It exists because CreateMemoryResourceNotification can trigger LowMemoryResourceNotifications
+    /// thousands of times per second when they happen (i.e. every system page allocation in a high memory pressure situation, often 4KB)
+    /// resulting in the memory list cleaning paralyzing the system with thousands of tries per second
+    /// In `poll` mode, this is the interval between two memory checks.
+    ///
+    /// Defaults to 10 seconds which should be enough for most systems without impacting performance.
+    #[clap(long = "islc-timer", default_value = "10")]
+    pub(crate) clean_standby_list_poll_freq: std::num::NonZeroU64,
+
+    /// Cached memory threshold where the Windows Standby List will be cleared.
+    /// Either a percentage of the total physical memory (`25%`) or a size with a unit (`MB`, `MiB`, `GB`, `GiB`...).
+    /// Values without a unit are in MB. Defaults to 1024MB (1GB)
+    #[clap(long = "cscm", default_value = "1024MB")]
+    pub(crate) clear_standby_cached_mem: standby::MemoryThreshold,
+
+    /// Free memory threshold where the Windows Standby List will be cleared.
+    /// Either a percentage of the total physical memory (`25%`) or a size with a unit (`MB`, `MiB`, `GB`, `GiB`...).
+    /// Values without a unit are in MB. Defaults to 1024MB (1GB)
+    #[clap(long = "csfm", default_value = "1024MB")]
+    pub(crate) clear_standby_free_mem: standby::MemoryThreshold,
+
+    /// Standby List priorities counted against the `--cscm` threshold, e.g. `0-4` to ignore high priority pages (5 to 7).
+    /// When restricted, the `standby` purge step only purges priority 0 pages (like `low-standby`)
+    /// whenever those alone exceed the threshold, on Windows only.
+    #[clap(long = "islc-priorities", value_name = "RANGE", default_value = "0-7")]
+    pub(crate) clean_standby_list_priorities: standby::PriorityRange,
+
+    /// Memory list commands issued, in order, when the Standby List cleaning conditions are met.
+    /// Comma-separated list of `working-sets`, `modified`, `low-standby` (priority 0 standby pages only) and `standby`.
+    /// Steps after the first one only run if free memory is still below the `--csfm` threshold,
+    /// e.g. `low-standby,standby` only purges the full Standby List when purging low priority pages wasn't enough.
+    #[clap(long = "islc-strategy", default_value = "standby")]
+    pub(crate) clean_standby_list_strategy: standby::PurgeStrategy,
+
+    /// Minimum time between two Standby List purges, in seconds.
+    #[clap(long = "islc-cooldown", value_name = "SECONDS", default_value = "0")]
+    pub(crate) clean_standby_list_cooldown: u64,
+
+    /// Free memory that has to be reached again after a Standby List purge before the next one can happen,
+    /// so that memory hovering around the `--csfm` threshold doesn't trigger purges over and over.
+    /// Same format as `--csfm`, and should be above it.
+    #[clap(long = "islc-rearm", value_name = "THRESHOLD")]
+    pub(crate) clean_standby_list_rearm: Option<standby::MemoryThreshold>,
+
+    /// Maximum number of Standby List purges within any hour.
+    #[clap(long = "islc-max-per-hour", value_name = "COUNT")]
+    pub(crate) clean_standby_list_max_per_hour: Option<usize>,
+
+    /// Comma-separated executable names that trigger a Standby List purge as soon as they start, e.g. games.
+    /// Case-insensitive; `*` matches any characters and `?` a single one, e.g. `game.exe,*-Win64-Shipping.exe`.
+    #[clap(long = "islc-processes", value_name = "PATTERNS")]
+    pub(crate) clean_standby_list_processes: Option<process::ProcessPatterns>,
+
+    /// Cached memory threshold applying instead of `--cscm` while an `--islc-processes` process runs.
+    #[clap(long = "islc-process-cscm", value_name = "THRESHOLD")]
+    pub(crate) clean_standby_list_process_cached_mem: Option<standby::MemoryThreshold>,
+
+    /// Free memory threshold applying instead of `--csfm` while an `--islc-processes` process runs.
+    #[clap(long = "islc-process-csfm", value_name = "THRESHOLD")]
+    pub(crate) clean_standby_list_process_free_mem: Option<standby::MemoryThreshold>,
+
+    /// Pauses Standby List cleaning while running on battery.
+    #[clap(long = "islc-pause-on-battery")]
+    pub(crate) clean_standby_list_pause_on_battery: bool,
+
+    /// Prints the possible timer value range for your system.
+    /// Please note that it can depend on many factors such as HPET or dynamic/synthetic timers enabled or disabled.
+    #[clap(short, long)]
+    pub(crate) values: bool,
+
+    /// How `--values` and the informational commands (`probe`, `bench`, `reset`, `simulate`) report their results:
+    /// `text` logs them, `json` prints a JSON document on stdout and sends logs to stderr.
+    #[clap(long, value_name = "FORMAT", default_value = "text", global = true)]
+    pub(crate) output: report::OutputFormat,
+
+    #[clap(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+    /// Measures sleep precision for each timer value between the bounds of your system and recommends one.
+    /// Helps choosing a `--timer` value empirically, since clock sources (TSC, HPET...) behave differently.
+    Bench {
+        /// Number of 1ms sleeps measured for each timer value
+        #[clap(long, default_value = "1000")]
+        samples: std::num::NonZeroUsize,
+    },
+    /// Applies timer values between the bounds of your system one after the other, and lists the distinct
+    /// resolutions the system actually grants.
+    Probe {
+        /// Interval between two probed timer values
+        #[clap(long, value_name = "TIMER", default_value = "50us")]
+        step: timer::TimerResolution,
+    },
+    /// Asks running timerset instances to shut down, which releases their timer resolution request,
+    /// then reports the resulting system timer resolution.
+    Reset,
+    /// Replays a `--record` file through the Standby List cleaning options (`--cscm`, `--csfm`, `--islc-timer`,
+    /// `--islc-strategy`...) and reports when purges would have happened and how much memory they would have reclaimed.
+    /// Never touches the system.
+    Simulate {
+        /// Recording to replay
+        file: std::path::PathBuf,
+        /// Physical memory of the recorded system, needed for percentage thresholds (e.g. `16GiB`)
+        #[clap(long, value_name = "SIZE")]
+        total_memory: Option<standby::MemoryThreshold>,
+    },
+}
+
+/// Longest time `reset` waits for running instances to exit
+const RESET_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Stops the other running timerset instances and reports the timer resolution they leave behind
+fn reset_timer(args: &Opts) -> TimersetResult<()> {
+    let instances = platform::running_instances()?;
+    if instances.is_empty() {
+        info!("No other timerset instance is running");
+    }
+    for &pid in &instances {
+        info!("Asking timerset instance {} to shut down", pid);
+        if !args.pretend {
+            platform::stop_instance(pid)?;
+        }
+    }
+
+    if !args.pretend {
+        let start = std::time::Instant::now();
+        while platform::running_instances()?
+            .iter()
+            .any(|pid| instances.contains(pid))
+        {
+            if start.elapsed() > RESET_TIMEOUT {
+                error!("timerset instances still running after {:?}", RESET_TIMEOUT);
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+    }
+
+    let timer_info = timer::TimerResolutionInfo::fetch()?;
+    let report = report::ResetReport {
+        stopped: instances,
+        cur_hns: timer_info.cur.as_hns(),
+    };
+    args.output.emit(&report, |_| {
+        info!("Timer resolution after reset: {}", timer_info.cur)
+    })
+}
+
+/// Builds a Standby List cleaner configured from the command line
+fn standby_list_cleaner<M: platform::MemoryBackend, N: platform::MemoryNotification>(
+    memory: M,
+    notification: N,
+    args: &Opts,
+) -> standby::StandbyListCleaner<M, N> {
+    standby::StandbyListCleaner::with_backends(memory, notification)
+        .standby_list_size_threshold(args.clear_standby_cached_mem)
+        .free_memory_size_threshold(args.clear_standby_free_mem)
+        .standby_priorities(args.clean_standby_list_priorities)
+        .purge_strategy(args.clean_standby_list_strategy.clone())
+        .purge_cooldown(args.clean_standby_list_cooldown)
+        .rearm_threshold(args.clean_standby_list_rearm)
+        .max_purges_per_hour(args.clean_standby_list_max_per_hour)
+        .process_standby_list_size_threshold(args.clean_standby_list_process_cached_mem)
+        .process_free_memory_size_threshold(args.clean_standby_list_process_free_mem)
+        .poll_interval(args.clean_standby_list_poll_freq.get())
+}
+
+/// Runs the Standby List cleaner as configured on the command line until shutdown
+fn monitor_standby_list<N: platform::MemoryNotification>(
+    notification: N,
+    args: &Opts,
+    shutdown: &shutdown::Shutdown,
+) -> TimersetResult<()> {
+    let mut cleaner = standby_list_cleaner(platform::NativeMemory::default(), notification, args);
+
+    if let Some(path) = &args.record {
+        info!("Recording memory samples to {:?}", path);
+        cleaner = cleaner.record_to(standby::MemoryRecorder::open(path)?);
+    }
+
+    if args.clean_standby_list_pause_on_battery {
+        cleaner =
+            cleaner.pause_on_battery(power::PowerWatcher::new(platform::NativePower::default()));
+    }
+
+    if let Some(patterns) = &args.clean_standby_list_processes {
+        info!("Purging the Standby List when any of {} starts", patterns);
+        cleaner = cleaner.watch_processes(process::ProcessWatcher::new(
+            patterns.clone(),
+            platform::NativeProcesses::default(),
+        ));
+    }
+
+    let result = cleaner.monitor_and_clean(shutdown);
+    standby::log_stats(cleaner.stats());
+    result
+}
+
+/// Replays a recording through the Standby List cleaner as configured on the command line
+fn simulate_standby_list(
+    file: &std::path::Path,
+    total_memory: Option<standby::MemoryThreshold>,
+    args: &Opts,
+) -> TimersetResult<()> {
+    let uses_percent = [
+        Some(args.clear_standby_cached_mem),
+        Some(args.clear_standby_free_mem),
+        args.clean_standby_list_rearm,
+    ]
+    .iter()
+    .any(|t| matches!(t, Some(standby::MemoryThreshold::Percent(_))));
+    let total = match total_memory {
+        Some(standby::MemoryThreshold::Percent(_)) => {
+            return Err(eyre::eyre!("--total-memory has to be a size, not a percentage").into())
+        }
+        Some(total) => total.resolve(0),
+        None if uses_percent => {
+            return Err(eyre::eyre!("--total-memory is needed for percentage thresholds").into())
+        }
+        None => 0,
+    };
+
+    let samples = standby::read_samples(file)?;
+    let mut cleaner = standby_list_cleaner(
+        standby::ReplayMemory::new(total),
+        platform::PollingNotification,
+        args,
+    );
+
+    let purges = cleaner.replay(&samples)?;
+    let report = report::SimulateReport::new(samples.len(), &purges)?;
+    args.output
+        .emit(&report, |_| standby::log_replay(&samples, &purges))
+}
+
+/// Runs timerset as configured on the command line
+pub fn run() -> TimersetResult<()> {
+    use clap::Parser as _;
+    let mut args = Opts::parse();
+
+    let mut logger = logger::Logger::new().stderr(args.output == report::OutputFormat::Json);
+    logger.init()?;
+
+    if args.pretend {
+        info!("--pretend enabled, no action will be taken on the system")
+    }
+
+    // Has to be set up before any thread gets spawned
+    let shutdown = shutdown::Shutdown::default();
+    platform::install_shutdown_handler(shutdown.clone())?;
+
+    match &args.command {
+        Some(Command::Simulate { file, total_memory }) => {
+            return simulate_standby_list(file, *total_memory, &args)
+        }
+        Some(Command::Reset) => return reset_timer(&args),
+        _ => {}
+    }
+
+    // Registry values changed by a previous run that didn't get to restore them
+    if !args.pretend && !args.values {
+        let mut journal = platform::RegistryJournal::new(
+            platform::NativeRegistry,
+            platform::registry_journal_path(),
+        );
+        if let Err(e) = journal.recover() {
+            warn!(
+                "Could not restore the registry journal {:?}: {}",
+                journal.journal_path(),
+                e
+            );
+        }
+    }
+
+    // Kept alive until exit: some platforms only hold the timer request as long as it lives
+    let mut timer_info = timer::TimerResolutionInfo::fetch()?;
+    info!("{}", timer_info);
+
+    if args.values {
+        let chosen = args
+            .timer
+            .map_or(timer_info.default_timer_value(), |timer| {
+                timer_info.clamp_timer_value(timer)
+            });
+        let memory = match report::MemoryReport::query(&mut platform::NativeMemory::default()) {
+            Ok(memory) => Some(memory),
+            Err(e) => {
+                info!("Could not query the memory lists: {}", e);
+                None
+            }
+        };
+        let report = report::ValuesReport {
+            timer: report::TimerReport::new(&timer_info, chosen),
+            memory,
+            install: install::status(
+                &platform::NativeInstaller,
+                &platform::NativeRegistry,
+                platform::os_build(),
+            )?,
+        };
+        return args.output.emit(&report, |report| {
+            info!("Chosen timer value: {}", chosen);
+            if !report.timer.system_wide {
+                info!(
+                    "Timer values are timerset's own timer slack, `cur` included: \
+                    only the wakeup latency request held alongside affects the rest of the system"
+                );
+            }
+            if let Some(memory) = &report.memory {
+                info!(
+                    "Memory: {}MB free / {}MB standby / {}MB modified, out of {}MB",
+                    memory.free_bytes / 1_000_000,
+                    memory.standby_bytes / 1_000_000,
+                    memory.modified_bytes / 1_000_000,
+                    memory.total_bytes / 1_000_000
+                );
+            }
+            match &report.install.install_dir {
+                Some(dir) if report.install.installed => info!("Installed at {:?}", dir),
+                _ => info!("Not installed"),
+            }
+            info!(
+                "GlobalTimerResolutionRequests: {}",
+                report.install.global_timer_requests
+            );
+        });
+    }
+
+    if let Some(Command::Bench { samples }) = args.command {
+        if args.pretend {
+            return Ok(());
+        }
+
+        let results = timer::bench::run_bench(
+            &mut timer_info,
+            &mut timer::bench::ThreadSleep,
+            samples.get(),
+        )?;
+        let recommended = timer::bench::recommend(&results);
+        let report = report::BenchReport::new(&results, recommended);
+        return args.output.emit(&report, |_| {
+            timer::bench::log_results(&results);
+            if let Some(recommended) = recommended {
+                info!("Recommended timer value: {}", recommended.requested);
+            }
+        });
+    }
+
+    if let Some(Command::Probe { step }) = args.command {
+        if args.pretend {
+            return Ok(());
+        }
+
+        let achievable = timer::probe::probe_achievable(&mut timer_info, step)?;
+        let report = report::ProbeReport::new(&achievable);
+        return args
+            .output
+            .emit(&report, |_| timer::probe::log_achievable(&achievable));
+    }
+
+    if let Some(timer) = args.timer.as_mut() {
+        *timer = timer_info.clamp_timer_value(*timer);
+        if args.snap && !timer_info.discrete_steps() {
+            info!("Any timer value can be granted on this system, not snapping");
+        } else if args.snap && !args.pretend {
+            // Probing changes the timer resolution, so it can't be done in pretend mode
+            let achievable =
+                timer::probe::probe_achievable(&mut timer_info, timer::probe::PROBE_STEP)?;
+            match timer::probe::snap_to_achievable(&achievable, *timer) {
+                Some(snapped) if snapped != *timer => {
+                    info!(
+                        "Timer value {} snapped to the closest achievable value: {}",
+                        timer, snapped
+                    );
+                    *timer = snapped;
+                }
+                Some(_) => {}
+                None => info!(
+                    "Every achievable timer value is finer than {}, keeping it",
+                    timer
+                ),
+            }
+        }
+    }
+
+    let timer_value = args
+        .timer
+        .unwrap_or_else(|| timer_info.default_timer_value());
+    // Timer profiles and the battery timer change the timer request as processes and the power source come and go
+    let timer_rules = !args.timer_profiles.is_empty() || args.battery_timer.is_some();
+
+    if !timer_rules {
+        info!("Chosen timer value: {}", timer_value);
+    } else {
+        for profile in &args.timer_profiles {
+            info!("Timer profile: {}", profile);
+        }
+        if let Some(battery_timer) = args.battery_timer {
+            info!("Timer on battery: {}", battery_timer);
+        }
+    }
+
+    if args.install || args.uninstall {
+        let mut installer = platform::NativeInstaller;
+        let mut install_journal = platform::RegistryJournal::new(
+            platform::NativeRegistry,
+            install::install_journal_path(),
+        );
+        if !platform::is_elevated() {
+            error!("You need to start this app with administrator permissions to install the program on your system.");
+        } else if args.install {
+            install::install(
+                &mut installer,
+                &mut install_journal,
+                platform::os_build(),
+                &args,
+            )?;
+        } else if args.uninstall {
+            // Revert install steps
+            let mut journal = platform::RegistryJournal::new(
+                platform::NativeRegistry,
+                platform::registry_journal_path(),
+            );
+            install::uninstall(&mut installer, &mut journal, &mut install_journal, &args)?;
+        }
+
+        return Ok(());
+    }
+
+    if !timer_rules {
+        if !args.pretend {
+            timer_info.apply_timer(timer_value)?;
+            if timer_info.cur != timer_value {
+                warn!(
+                    "Requested timer value {} but the system applied {}",
+                    timer_value, timer_info.cur
+                );
+            }
+        }
+        info!("New timer value set: {}", timer_info.cur);
+    }
+
+    // Timer profiles or the watchdog take over the timer until shutdown, then hand it back to be released
+    let mut timer_info = Some(timer_info);
+    let timer_thread = match (args.timer_watchdog, timer_info.take()) {
+        (_, Some(info)) if timer_rules && !args.pretend => {
+            let mut profiles = timer::TimerProfiles::new(
+                info,
+                args.timer_profiles.clone(),
+                platform::NativeProcesses::default(),
+            )
+            // Without profiles, the chosen timer value is held whenever the battery timer doesn't apply
+            .fallback(if args.timer_profiles.is_empty() {
+                Some(timer_value)
+            } else {
+                args.timer
+            });
+            if let Some(battery_timer) = args.battery_timer {
+                profiles = profiles.on_battery(
+                    battery_timer,
+                    power::PowerWatcher::new(platform::NativePower::default()),
+                );
+            }
+            let shutdown = shutdown.clone();
+            Some(std::thread::spawn(move || {
+                profiles.run(&shutdown);
+                profiles.into_info()
+            }))
+        }
+        (Some(interval), Some(info)) if !args.pretend => {
+            let mut watchdog =
+                timer::TimerWatchdog::new(info, timer_value).interval(interval.get());
+            let shutdown = shutdown.clone();
+            Some(std::thread::spawn(move || {
+                watchdog.run(&shutdown);
+                watchdog.into_info()
+            }))
+        }
+        (_, info) => {
+            timer_info = info;
+            None
+        }
+    };
+
+    let monitored = if args.clean_standby_list {
+        if !platform::is_elevated() {
+            error!("You need to start this app with administrator permissions to use the standby list cleaning feature.");
+            return Ok(());
+        }
+
+        if args.pretend {
+            return Ok(());
+        }
+        info!(
+            "Cleaned up resources and starting memory monitoring in {} mode...",
+            args.clean_standby_list_mode
+        );
+        let monitored = match args.clean_standby_list_mode {
+            standby::CleanerMode::Notification => {
+                monitor_standby_list(platform::NativeNotification::default(), &args, &shutdown)
+            }
+            standby::CleanerMode::Poll => {
+                monitor_standby_list(platform::PollingNotification, &args, &shutdown)
+            }
+        };
+        if monitored.is_err() {
+            // Stops the timer threads too, so that they can be joined below
+            shutdown.request(shutdown::ShutdownReason::Terminate);
+        }
+        monitored
+    } else {
+        if args.pretend {
+            return Ok(());
+        }
+        info!("Cleaned up resources and waiting until shutdown...");
+        shutdown.wait();
+        Ok(())
+    };
+
+    if let Some(reason) = shutdown.requested() {
+        info!("Shutting down: {}", reason);
+    }
+
+    // Platform handlers wait for cleaning up to complete, which has to happen whatever failed before
+    let released = release_timer(timer_thread, timer_info);
+    log::logger().flush();
+    shutdown.complete();
+
+    monitored.and(released)
+}
+
+/// Joins the timer thread if any, then releases the timer request it or the main thread held
+fn release_timer(
+    timer_thread: Option<std::thread::JoinHandle<timer::TimerResolutionInfo>>,
+    mut timer_info: Option<timer::TimerResolutionInfo>,
+) -> TimersetResult<()> {
+    if let Some(handle) = timer_thread {
+        timer_info = Some(
+            handle
+                .join()
+                .map_err(|_| eyre::eyre!("Timer thread panicked"))?,
+        );
+    }
+    if let Some(timer_info) = timer_info.as_mut() {
+        timer_info.release_timer()?;
+        info!(
+            "Released timer resolution request, now at {}",
+            timer_info.cur
+        );
+    }
+    Ok(())
+}
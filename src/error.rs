@@ -1,5 +1,6 @@
 #[derive(Debug, thiserror::Error)]
 pub enum TimersetError {
+    #[cfg(windows)]
     #[error(transparent)]
     TaskSchedulerError(#[from] crate::task_scheduler::TaskSchedulerError),
     #[error(transparent)]
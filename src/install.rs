@@ -1,40 +1,51 @@
-use crate::task_scheduler::{ExecAction, RegisterTaskDefinitionArgs, WindowsTaskScheduler};
+use crate::platform::{RegistryBackend, RegistryJournal, TaskInstaller};
+use crate::report::InstallReport;
+use crate::timer::GlobalTimerRequests;
+use crate::utils::StartArgs;
 use crate::TimersetResult;
-use crate::{
-    task_scheduler::{
-        LogonTrigger, TaskActionType, TaskCompatibility, TaskInstancesPolicy, TaskLogonType,
-        TaskRunlevel, TaskTriggerType,
-    },
-    utils::StartArgs,
-};
-use log::{debug, info};
-use winapi::um::taskschd::TASK_CREATE_OR_UPDATE;
+use log::{debug, info, warn};
 
 #[cfg(debug)]
 const TASK_NAME: &str = "Start TimerSet [DEV]";
 #[cfg(not(debug))]
 const TASK_NAME: &str = "Start TimerSet";
 
-pub fn install(args: &crate::Opts) -> crate::TimersetResult<()> {
-    // Copy exe to %ProgramFiles%\TimerSet\TimerSet.exe
-    let current_exe_path = std::env::current_exe()?;
-    debug!("Current exe path: {:?}", current_exe_path);
+/// Location of the journal of registry values changed on install.
+/// It is kept apart from the registry journal of running instances, which restore it every time they start.
+pub fn install_journal_path() -> std::path::PathBuf {
+    crate::platform::registry_journal_path().with_file_name("install.journal")
+}
 
-    let mut dest_path: std::path::PathBuf = std::env::var("PROGRAMFILES")?.into();
-    dest_path.push("TimerSet");
+/// Reports whether timerset is installed, and whether its startup task can change the timer resolution
+pub fn status<I: TaskInstaller, R: RegistryBackend>(
+    installer: &I,
+    registry: &R,
+    build: Option<u32>,
+) -> TimersetResult<InstallReport> {
+    let install_dir = installer.install_dir().ok();
+    Ok(InstallReport {
+        installed: install_dir
+            .as_ref()
+            .is_some_and(|dir| dir.join("TimerSet.exe").exists()),
+        install_dir,
+        global_timer_requests: GlobalTimerRequests::check(registry, build)?,
+    })
+}
+
+/// Installs timerset as a startup task. `build` is the Windows build number, if any.
+pub fn install<I: TaskInstaller, R: RegistryBackend>(
+    installer: &mut I,
+    journal: &mut RegistryJournal<R>,
+    build: Option<u32>,
+    args: &crate::Opts,
+) -> TimersetResult<()> {
+    // Copy exe to %ProgramFiles%\TimerSet\TimerSet.exe
+    let mut dest_path = installer.install_dir()?;
     info!("Installing TimerSet at: {:?}", dest_path);
-    debug!("Creating app folder");
-    if !args.pretend {
-        std::fs::create_dir_all(dest_path.clone())?;
-    }
     dest_path.push("TimerSet.exe");
 
-    debug!(
-        "Moving timerset.exe from {:?} to {:?}",
-        current_exe_path, dest_path
-    );
     if !args.pretend {
-        std::fs::copy(current_exe_path, dest_path.clone())?;
+        installer.copy_executable(&dest_path)?;
     }
 
     let start_args = StartArgs::build_from_args(dest_path, args);
@@ -42,57 +53,23 @@ pub fn install(args: &crate::Opts) -> crate::TimersetResult<()> {
     debug!("Built start args: {}", start_args);
 
     if !args.pretend {
-        let scheduler = WindowsTaskScheduler::new()?;
-        scheduler.connect()?;
-
-        let folder = scheduler.folder("\\")?;
-        folder.delete_task(TASK_NAME)?;
-        let task = scheduler.new_task()?;
-
-        let task_principal = task.principal()?;
-        task_principal.set_group_id("NT AUTHORITY\\SYSTEM")?;
-        task_principal.set_logon_type(TaskLogonType::Group)?;
-        task_principal.set_runlevel(TaskRunlevel::Highest)?;
-
-        let task_reginfo = task.registration_info()?;
-        task_reginfo.set_author("Mathieu \"OtaK_\" Amiot")?;
-        task_reginfo
-            .set_description("Start TimerSet at logon of any user with admin permissions")?;
-
-        let task_settings = task.settings()?;
-        task_settings.set_start_when_available(true)?;
-        task_settings.set_enabled(true)?;
-        task_settings.set_hidden(false)?;
-        task_settings.set_multiple_instances(TaskInstancesPolicy::StopExisting)?;
-        task_settings.set_execution_time_limit("PT0S")?;
-        task_settings.set_compatibility(TaskCompatibility::V24)?;
-
-        let triggers = task.triggers()?;
-        let raw_trigger = triggers.create(TaskTriggerType::Logon)?;
-        use crate::task_scheduler::SubTrigger as _;
-        let logon_trigger = LogonTrigger::new(raw_trigger)?;
-        logon_trigger.trigger().set_execution_time_limit("PT0S")?;
-        logon_trigger.set_delay("PT10S")?;
-
-        let action_collection = task.actions()?;
-        let raw_task = action_collection.create(TaskActionType::Exec)?;
-        use crate::task_scheduler::SubAction as _;
-        let exec_action = ExecAction::new(raw_task)?;
-        exec_action.set_path(start_args.target)?;
-        exec_action.set_arguments(StartArgs::args_to_string(&start_args.args))?;
-        if let Some(start_location) = start_args.start_location {
-            exec_action.set_working_directory(start_location)?;
-        }
+        installer.register_startup_task(TASK_NAME, &start_args)?;
+    }
 
-        let _ = folder.register_task_definition(RegisterTaskDefinitionArgs {
-            task_name: TASK_NAME,
-            task_definition: task,
-            flags: TASK_CREATE_OR_UPDATE as _,
-            user_id: None,
-            password: None,
-            logon_type: TaskLogonType::InteractiveToken,
-            sddl: None,
-        })?;
+    // The startup task runs in the background, where timer requests are ignored unless told otherwise
+    match GlobalTimerRequests::check(journal.registry(), build)? {
+        GlobalTimerRequests::Disabled if args.global_timer_requests => {
+            info!("Enabling GlobalTimerResolutionRequests, which takes effect after a reboot");
+            if !args.pretend {
+                GlobalTimerRequests::enable(journal)?;
+            }
+        }
+        GlobalTimerRequests::Disabled => warn!(
+            "GlobalTimerResolutionRequests is {}: the installed task won't change the timer resolution. \
+            Install with --global-timer-requests to enable it",
+            GlobalTimerRequests::Disabled
+        ),
+        status => info!("GlobalTimerResolutionRequests: {}", status),
     }
 
     info!("Installation complete");
@@ -100,21 +77,31 @@ pub fn install(args: &crate::Opts) -> crate::TimersetResult<()> {
     Ok(())
 }
 
-pub fn uninstall(args: &crate::Opts) -> TimersetResult<()> {
-    let scheduler = WindowsTaskScheduler::new()?;
-    scheduler.connect()?;
-
-    let folder = scheduler.folder("\\")?;
+pub fn uninstall<I: TaskInstaller, R: RegistryBackend>(
+    installer: &mut I,
+    journal: &mut RegistryJournal<R>,
+    install_journal: &mut RegistryJournal<R>,
+    args: &crate::Opts,
+) -> TimersetResult<()> {
     if !args.pretend {
-        folder.delete_task(TASK_NAME)?;
+        installer.delete_startup_task(TASK_NAME)?;
+    }
+
+    // Put back whatever registry values were there before timerset touched them
+    debug!("Registry journal: {:?}", journal.journal_path());
+    if !args.pretend && journal.restore()? {
+        info!("Restored original registry values");
+    }
+    debug!("Install journal: {:?}", install_journal.journal_path());
+    if !args.pretend && install_journal.restore()? {
+        info!("Restored registry values changed on install");
     }
 
     // Delete files
-    let mut dest_path: std::path::PathBuf = std::env::var("PROGRAMFILES")?.into();
-    dest_path.push("TimerSet");
+    let dest_path = installer.install_dir()?;
     debug!("Installation path to be removed: {:?}", dest_path);
     if !args.pretend {
-        let _ = std::fs::remove_dir_all(dest_path);
+        installer.remove_install_dir(&dest_path)?;
     }
 
     info!("Uninstall complete.");
@@ -123,6 +110,24 @@ pub fn uninstall(args: &crate::Opts) -> TimersetResult<()> {
 
 #[cfg(test)]
 mod test {
+    use crate::platform::{
+        sim::{SimInstaller, SimRegistry},
+        RegistryBackend as _, RegistryJournal, TaskInstaller as _, KERNEL_KEY,
+        MEMORY_MANAGEMENT_KEY,
+    };
+    use crate::timer::GLOBAL_TIMER_REQUESTS_VALUE;
+
+    fn journal(test: &str) -> (SimRegistry, RegistryJournal<SimRegistry>) {
+        let path = std::env::temp_dir().join(format!(
+            "timerset-install-journal-{}-{}",
+            test,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let registry = SimRegistry::default();
+        (registry.clone(), RegistryJournal::new(registry, path))
+    }
+
     #[test]
     fn should_format_args_correctly() {
         let test_args = super::StartArgs {
@@ -135,4 +140,118 @@ mod test {
             "\"timerset.exe\" --arg1 --arg2 --arg3 withvalue"
         );
     }
+
+    #[test]
+    fn should_install_and_uninstall() {
+        use clap::Parser as _;
+        let mut installer = SimInstaller::default();
+        let install_dir = installer.install_dir().unwrap();
+        let args = crate::Opts::parse_from([
+            "timerset",
+            "--install",
+            "--timer",
+            "0.5ms",
+            "--islc",
+            "--cscm",
+            "2048",
+            "--csfm",
+            "25%",
+            "--islc-rearm",
+            "30%",
+            "--islc-max-per-hour",
+            "4",
+        ]);
+
+        // Left behind by a running instance that never got to clean up
+        let (mut registry, mut journal) = journal("uninstall");
+        registry
+            .set_u32(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold", 64)
+            .unwrap();
+        journal
+            .set_u32(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold", 1024)
+            .unwrap();
+        let mut install_journal = RegistryJournal::new(
+            registry.clone(),
+            journal.journal_path().with_extension("install"),
+        );
+
+        super::install(&mut installer, &mut install_journal, None, &args).unwrap();
+        {
+            let state = installer.state();
+            assert_eq!(state.copied_to, vec![install_dir.join("TimerSet.exe")]);
+            let task = &state.tasks[super::TASK_NAME];
+            assert_eq!(
+                task.args,
+                vec![
+                    "--timer 5000hns",
+                    "--islc",
+                    "--cscm 2048MB",
+                    "--csfm 25%",
+                    "--islc-rearm 30%",
+                    "--islc-max-per-hour 4"
+                ]
+            );
+            assert_eq!(task.start_location, install_dir.to_str().map(Into::into));
+        }
+
+        super::uninstall(&mut installer, &mut journal, &mut install_journal, &args).unwrap();
+        let state = installer.state();
+        assert!(state.tasks.is_empty());
+        assert_eq!(state.removed_dirs, vec![install_dir]);
+        assert_eq!(
+            registry.value(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold"),
+            Some(64)
+        );
+        assert!(!journal.journal_path().exists());
+    }
+
+    #[test]
+    fn should_not_touch_the_system_when_pretending() {
+        use clap::Parser as _;
+        let mut installer = SimInstaller::default();
+        let args = crate::Opts::parse_from(["timerset", "--install", "--pretend"]);
+        let (registry, mut journal) = journal("pretend");
+        let mut install_journal = journal.clone();
+
+        super::install(&mut installer, &mut install_journal, Some(22631), &args).unwrap();
+        super::uninstall(&mut installer, &mut journal, &mut install_journal, &args).unwrap();
+        let state = installer.state();
+        assert!(state.copied_to.is_empty());
+        assert!(state.tasks.is_empty());
+        assert!(state.removed_dirs.is_empty());
+        assert!(registry.values().is_empty());
+    }
+
+    #[test]
+    fn should_enable_and_revert_global_timer_requests() {
+        use clap::Parser as _;
+        let mut installer = SimInstaller::default();
+        let (registry, mut journal) = journal("global-timer-requests");
+        let mut install_journal = RegistryJournal::new(
+            registry.clone(),
+            journal.journal_path().with_extension("install"),
+        );
+        let value = || registry.value(KERNEL_KEY, GLOBAL_TIMER_REQUESTS_VALUE);
+
+        // Only reported unless asked for
+        let args = crate::Opts::parse_from(["timerset", "--install"]);
+        super::install(&mut installer, &mut install_journal, Some(22631), &args).unwrap();
+        assert_eq!(value(), None);
+
+        // Not needed before Windows Server 2022
+        let args = crate::Opts::parse_from(["timerset", "--install", "--global-timer-requests"]);
+        super::install(&mut installer, &mut install_journal, Some(19045), &args).unwrap();
+        assert_eq!(value(), None);
+
+        super::install(&mut installer, &mut install_journal, Some(22631), &args).unwrap();
+        assert_eq!(value(), Some(1));
+
+        // Running instances restoring their own journal leave it alone
+        journal.recover().unwrap();
+        assert_eq!(value(), Some(1));
+
+        let args = crate::Opts::parse_from(["timerset", "--uninstall"]);
+        super::uninstall(&mut installer, &mut journal, &mut install_journal, &args).unwrap();
+        assert_eq!(value(), None);
+    }
 }
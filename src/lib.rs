@@ -0,0 +1,25 @@
+//! TimerSet allows you to change your NT Kernel system timer, and to monitor the Windows Standby List
+//! and clean it up when needed.
+//!
+//! Besides the `timerset` executable, the [`timer`] module is available as a library, e.g. to hold
+//! a timer resolution for the duration of a scope with a [`timer::TimerResolutionGuard`].
+
+mod utils;
+
+mod cli;
+mod error;
+mod install;
+mod logger;
+mod macros;
+mod platform;
+mod power;
+mod process;
+mod report;
+mod shutdown;
+mod standby;
+#[cfg(windows)]
+mod task_scheduler;
+pub mod timer;
+pub use self::cli::run;
+pub(crate) use self::cli::Opts;
+pub use self::error::*;
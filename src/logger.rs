@@ -3,11 +3,21 @@ use fern::colors::{Color, ColoredLevelConfig};
 #[derive(Debug)]
 pub struct Logger {
     has_init: bool,
+    stderr: bool,
 }
 
 impl Logger {
     pub fn new() -> Self {
-        Self { has_init: false }
+        Self {
+            has_init: false,
+            stderr: false,
+        }
+    }
+
+    /// Sends console logs to stderr instead of stdout, which is then left to machine-readable output
+    pub fn stderr(mut self, stderr: bool) -> Self {
+        self.stderr = stderr;
+        self
     }
 
     pub fn init(&mut self) -> crate::TimersetResult<()> {
@@ -16,7 +26,7 @@ impl Logger {
         }
 
         fern::Dispatch::new()
-            .chain(stdout()?)
+            .chain(console(self.stderr)?)
             .chain(filelog()?)
             .apply()?;
 
@@ -26,7 +36,7 @@ impl Logger {
     }
 }
 
-fn stdout() -> crate::TimersetResult<fern::Dispatch> {
+fn console(stderr: bool) -> crate::TimersetResult<fern::Dispatch> {
     let colors = ColoredLevelConfig::new()
         .error(Color::Red)
         .warn(Color::Yellow)
@@ -49,8 +59,13 @@ fn stdout() -> crate::TimersetResult<fern::Dispatch> {
                 message = message,
             ))
         })
-        .level(log::LevelFilter::Info)
-        .chain(std::io::stdout());
+        .level(log::LevelFilter::Info);
+
+    let dispatcher = if stderr {
+        dispatcher.chain(std::io::stderr())
+    } else {
+        dispatcher.chain(std::io::stdout())
+    };
 
     Ok(dispatcher)
 }
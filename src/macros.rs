@@ -30,7 +30,7 @@ macro_rules! w32_ok {
     }};
 
     (DEBUG $call:expr, $debug_call:expr, ELSE $else_call:expr) => {{
-        match crate::w32_ok!(DEBUG $call, $debug_call) {
+        match $crate::w32_ok!(DEBUG $call, $debug_call) {
             Ok(a) => Ok(a),
             Err(e) => {
                 unsafe { $else_call };
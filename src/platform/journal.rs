@@ -0,0 +1,397 @@
+use std::path::{Path, PathBuf};
+
+use log::{debug, info, warn};
+
+use crate::platform::RegistryBackend;
+
+/// Registry wrapper saving the original value of everything it modifies to an on-disk journal
+/// before touching it, so that it can be restored even if timerset never got to clean up after itself.
+///
+/// Only one process owns the journal at a time: values can't be modified nor restored while another
+/// timerset instance that modified them still runs.
+///
+/// Journal format is a `pid\t<pid>\t<start time>` line naming the process that wrote it, then one line
+/// per value: `path\tname\tvalue`, with `-` as value when it did not exist.
+#[derive(Debug, Clone)]
+pub struct RegistryJournal<R: RegistryBackend> {
+    registry: R,
+    path: PathBuf,
+}
+
+/// Process that wrote a journal. Its start time tells it apart from a later process reusing its ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct JournalOwner {
+    pid: u32,
+    started: u64,
+}
+
+impl JournalOwner {
+    fn current() -> Self {
+        let pid = std::process::id();
+        Self {
+            pid,
+            started: crate::platform::process_start_time(pid).unwrap_or_default(),
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        crate::platform::process_start_time(self.pid) == Some(self.started)
+    }
+}
+
+impl std::str::FromStr for JournalOwner {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (pid, started) = s
+            .split_once('\t')
+            .ok_or_else(|| eyre::eyre!("Malformed registry journal owner: {:?}", s))?;
+        Ok(Self {
+            pid: pid.parse()?,
+            started: started.parse()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct JournalEntry {
+    path: String,
+    name: String,
+    original: Option<u32>,
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+impl<R: RegistryBackend> RegistryJournal<R> {
+    pub fn new(registry: R, path: impl Into<PathBuf>) -> Self {
+        Self {
+            registry,
+            path: path.into(),
+        }
+    }
+
+    pub fn journal_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Journaled entries, along with the process that wrote them
+    fn read_entries(&self) -> crate::TimersetResult<(Option<JournalOwner>, Vec<JournalEntry>)> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((None, vec![])),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut lines = contents.lines().filter(|line| !line.is_empty()).peekable();
+        let owner = match lines.peek().and_then(|line| line.strip_prefix("pid\t")) {
+            Some(owner) => {
+                lines.next();
+                Some(owner.parse()?)
+            }
+            None => None,
+        };
+
+        let entries = lines
+            .map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let (path, name, original) = match (fields.next(), fields.next(), fields.next()) {
+                    (Some(path), Some(name), Some(original)) => (path, name, original),
+                    _ => eyre::bail!("Malformed registry journal line: {:?}", line),
+                };
+                let original = match original {
+                    "-" => None,
+                    value => Some(value.parse()?),
+                };
+                Ok(JournalEntry {
+                    path: path.into(),
+                    name: name.into(),
+                    original,
+                })
+            })
+            .collect::<eyre::Result<_>>()?;
+        Ok((owner, entries))
+    }
+
+    fn write_entries(&self, entries: &[JournalEntry]) -> crate::TimersetResult<()> {
+        use std::io::Write as _;
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = std::fs::File::create(&self.path)?;
+        let owner = JournalOwner::current();
+        writeln!(file, "pid\t{}\t{}", owner.pid, owner.started)?;
+        for entry in entries {
+            match entry.original {
+                Some(value) => writeln!(file, "{}\t{}\t{}", entry.path, entry.name, value)?,
+                None => writeln!(file, "{}\t{}\t-", entry.path, entry.name)?,
+            }
+        }
+        // The journal has to hit the disk before the registry gets modified
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// Underlying registry, for reads
+    pub fn registry(&self) -> &R {
+        &self.registry
+    }
+
+    /// Another running timerset instance owning the journal, if any
+    fn other_owner(owner: Option<JournalOwner>) -> Option<JournalOwner> {
+        owner.filter(|owner| *owner != JournalOwner::current() && owner.is_running())
+    }
+
+    /// Journals the current value if it isn't already, then overwrites it.
+    /// Fails while another running timerset instance owns the journal.
+    pub fn set_u32(&mut self, path: &str, name: &str, value: u32) -> crate::TimersetResult<()> {
+        let (owner, mut entries) = self.read_entries()?;
+        if let Some(owner) = Self::other_owner(owner) {
+            return Err(eyre::eyre!(
+                "Registry journal {:?} is in use by timerset instance {}",
+                self.path,
+                owner.pid
+            )
+            .into());
+        }
+
+        // Journals left over by a process that exited get taken over, keeping their original values
+        let take_over = owner != Some(JournalOwner::current());
+        if !entries.iter().any(|e| e.path == path && e.name == name) {
+            let original = self.registry.get_u32(path, name)?;
+            debug!("Journaling original {}\\{}: {:?}", path, name, original);
+            entries.push(JournalEntry {
+                path: path.into(),
+                name: name.into(),
+                original,
+            });
+            self.write_entries(&entries)?;
+        } else if take_over {
+            self.write_entries(&entries)?;
+        }
+
+        self.registry.set_u32(path, name, value)
+    }
+
+    /// Restores every journaled value to what it was before being modified, then removes the journal.
+    /// Returns whether there was anything to restore: journals owned by another running timerset
+    /// instance are left alone, since it still depends on the modified values.
+    pub fn restore(&mut self) -> crate::TimersetResult<bool> {
+        let (owner, entries) = self.read_entries()?;
+        if entries.is_empty() {
+            return Ok(false);
+        }
+        if let Some(owner) = Self::other_owner(owner) {
+            info!(
+                "Registry journal {:?} belongs to running instance {}, leaving it alone",
+                self.path, owner.pid
+            );
+            return Ok(false);
+        }
+
+        for entry in &entries {
+            debug!(
+                "Restoring {}\\{} to {:?}",
+                entry.path, entry.name, entry.original
+            );
+            match entry.original {
+                Some(value) => self.registry.set_u32(&entry.path, &entry.name, value)?,
+                None => match self.registry.delete_value(&entry.path, &entry.name) {
+                    Err(crate::TimersetError::IoError(e))
+                        if e.kind() == std::io::ErrorKind::NotFound => {}
+                    result => result?,
+                },
+            }
+        }
+
+        std::fs::remove_file(&self.path)?;
+        Ok(true)
+    }
+
+    /// Restores the values left over by a previous run that did not exit cleanly.
+    /// Journals written by another timerset instance that is still running are left alone.
+    pub fn recover(&mut self) -> crate::TimersetResult<()> {
+        if self.restore()? {
+            warn!(
+                "Restored registry values left over by a previous run from {:?}",
+                self.path
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RegistryJournal;
+    use crate::platform::{sim::SimRegistry, RegistryBackend as _, MEMORY_MANAGEMENT_KEY};
+
+    fn journal(test: &str) -> (SimRegistry, RegistryJournal<SimRegistry>) {
+        let path =
+            std::env::temp_dir().join(format!("timerset-journal-{}-{}", test, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let registry = SimRegistry::default();
+        (registry.clone(), RegistryJournal::new(registry, path))
+    }
+
+    #[test]
+    fn should_restore_original_values() {
+        let (mut registry, mut journal) = journal("restore");
+        registry
+            .set_u32(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold", 64)
+            .unwrap();
+
+        journal
+            .set_u32(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold", 1024)
+            .unwrap();
+        journal
+            .set_u32(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold", 2048)
+            .unwrap();
+        journal
+            .set_u32(MEMORY_MANAGEMENT_KEY, "Missing", 1)
+            .unwrap();
+        assert_eq!(
+            registry.value(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold"),
+            Some(2048)
+        );
+        assert!(journal.journal_path().exists());
+
+        assert!(journal.restore().unwrap());
+        assert_eq!(
+            registry.value(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold"),
+            Some(64)
+        );
+        assert_eq!(registry.value(MEMORY_MANAGEMENT_KEY, "Missing"), None);
+        assert!(!journal.journal_path().exists());
+        assert!(!journal.restore().unwrap());
+    }
+
+    #[test]
+    fn should_recover_journal_left_by_crashed_run() {
+        let (registry, mut crashed) = journal("recover");
+        crashed
+            .set_u32(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold", 1024)
+            .unwrap();
+        // The process dies without restoring anything, next run picks up the journal
+        let mut journal = RegistryJournal::new(registry.clone(), crashed.journal_path());
+        journal.recover().unwrap();
+        assert_eq!(
+            registry.value(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold"),
+            None
+        );
+        assert!(!journal.journal_path().exists());
+    }
+
+    #[test]
+    fn should_recover_journal_of_exited_instance() {
+        let (mut registry, mut journal) = journal("exited");
+        registry
+            .set_u32(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold", 1024)
+            .unwrap();
+        // No process ever gets that ID
+        std::fs::write(
+            journal.journal_path(),
+            format!(
+                "pid\t{}\t0\n{}\tLowMemoryThreshold\t64\n",
+                i32::MAX,
+                MEMORY_MANAGEMENT_KEY
+            ),
+        )
+        .unwrap();
+
+        journal.recover().unwrap();
+        assert_eq!(
+            registry.value(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold"),
+            Some(64)
+        );
+        assert!(!journal.journal_path().exists());
+    }
+
+    /// Journal held by the process that runs the tests, started at `started`
+    #[cfg(unix)]
+    fn write_parent_journal(journal: &RegistryJournal<SimRegistry>, started: u64) {
+        std::fs::write(
+            journal.journal_path(),
+            format!(
+                "pid\t{}\t{}\n{}\tLowMemoryThreshold\t64\n",
+                std::os::unix::process::parent_id(),
+                started,
+                MEMORY_MANAGEMENT_KEY
+            ),
+        )
+        .unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_leave_journal_of_running_instance_alone() {
+        let (mut registry, mut journal) = journal("running");
+        registry
+            .set_u32(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold", 1024)
+            .unwrap();
+        // The test runner stands in for another instance still holding the journal
+        let started =
+            crate::platform::process_start_time(std::os::unix::process::parent_id()).unwrap();
+        write_parent_journal(&journal, started);
+
+        journal.recover().unwrap();
+        assert!(!journal.restore().unwrap());
+        assert!(journal
+            .set_u32(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold", 2048)
+            .is_err());
+        assert_eq!(
+            registry.value(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold"),
+            Some(1024)
+        );
+        assert!(journal.journal_path().exists());
+        std::fs::remove_file(journal.journal_path()).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn should_recover_journal_of_reused_process_id() {
+        let (mut registry, mut journal) = journal("reused");
+        registry
+            .set_u32(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold", 1024)
+            .unwrap();
+        // Same ID as a running process, but that process started at another time
+        let started =
+            crate::platform::process_start_time(std::os::unix::process::parent_id()).unwrap();
+        write_parent_journal(&journal, started + 1);
+
+        journal.recover().unwrap();
+        assert_eq!(
+            registry.value(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold"),
+            Some(64)
+        );
+        assert!(!journal.journal_path().exists());
+    }
+
+    #[test]
+    fn should_take_over_journal_of_exited_instance() {
+        let (mut registry, mut journal) = journal("takeover");
+        registry
+            .set_u32(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold", 64)
+            .unwrap();
+        std::fs::write(
+            journal.journal_path(),
+            format!(
+                "pid\t{}\t0\n{}\tLowMemoryThreshold\t64\n",
+                i32::MAX,
+                MEMORY_MANAGEMENT_KEY
+            ),
+        )
+        .unwrap();
+
+        journal
+            .set_u32(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold", 1024)
+            .unwrap();
+        let content = std::fs::read_to_string(journal.journal_path()).unwrap();
+        assert!(content.starts_with(&format!("pid\t{}\t", std::process::id())));
+        assert!(journal.restore().unwrap());
+        assert_eq!(
+            registry.value(MEMORY_MANAGEMENT_KEY, "LowMemoryThreshold"),
+            Some(64)
+        );
+    }
+}
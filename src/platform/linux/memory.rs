@@ -0,0 +1,238 @@
+use std::path::PathBuf;
+
+use log::debug;
+
+use crate::platform::{MemoryBackend, MemoryListInfo, PurgeCommand};
+
+const MEMINFO_PATH: &str = "/proc/meminfo";
+const DROP_CACHES_PATH: &str = "/proc/sys/vm/drop_caches";
+
+/// Standby priority the reclaimable `Inactive(file)` part of the page cache is reported at
+const INACTIVE_CACHE_PRIORITY: usize = 2;
+/// Standby priority the rest of the page cache is reported at
+const ACTIVE_CACHE_PRIORITY: usize = 5;
+
+/// The subset of `/proc/meminfo` timerset cares about, in bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemInfo {
+    pub mem_total: u64,
+    pub mem_free: u64,
+    pub cached: u64,
+    pub inactive_file: u64,
+    pub dirty: u64,
+}
+
+impl std::str::FromStr for MemInfo {
+    type Err = std::io::Error;
+
+    /// Parses the contents of `/proc/meminfo`, made of `Key:   value kB` lines
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |line: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid meminfo line: {:?}", line),
+            )
+        };
+
+        let mut meminfo = Self::default();
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, value) = line.split_once(':').ok_or_else(|| invalid(line))?;
+            let field = match key.trim() {
+                "MemTotal" => &mut meminfo.mem_total,
+                "MemFree" => &mut meminfo.mem_free,
+                "Cached" => &mut meminfo.cached,
+                "Inactive(file)" => &mut meminfo.inactive_file,
+                "Dirty" => &mut meminfo.dirty,
+                _ => continue,
+            };
+
+            let mut parts = value.split_whitespace();
+            let amount: u64 = parts
+                .next()
+                .and_then(|amount| amount.parse().ok())
+                .ok_or_else(|| invalid(line))?;
+            *field = match parts.next() {
+                Some("kB") => amount * 1024,
+                None => amount,
+                Some(_) => return Err(invalid(line)),
+            };
+        }
+
+        Ok(meminfo)
+    }
+}
+
+impl MemInfo {
+    /// Maps the page cache onto the standby list: the inactive part of the cache is the one the kernel
+    /// reclaims first, so it's reported as low priority standby memory and the rest as normal priority.
+    pub fn to_memory_lists(self, page_size: usize) -> MemoryListInfo {
+        let page_size = page_size as u64;
+        let inactive = self.inactive_file.min(self.cached);
+        let mut lists = MemoryListInfo {
+            free_page_count: (self.mem_free / page_size) as usize,
+            modified_page_count: (self.dirty / page_size) as usize,
+            ..Default::default()
+        };
+        lists.page_count_by_priority[INACTIVE_CACHE_PRIORITY] = (inactive / page_size) as usize;
+        lists.page_count_by_priority[ACTIVE_CACHE_PRIORITY] =
+            ((self.cached - inactive) / page_size) as usize;
+        lists
+    }
+}
+
+/// Linux memory lists: the page cache stands in for the standby list, and is purged through `drop_caches`
+#[derive(Debug)]
+pub struct LinuxMemory {
+    page_size: usize,
+    meminfo_path: PathBuf,
+    drop_caches_path: PathBuf,
+}
+
+impl Default for LinuxMemory {
+    fn default() -> Self {
+        Self::with_paths(MEMINFO_PATH, DROP_CACHES_PATH)
+    }
+}
+
+impl LinuxMemory {
+    /// Uses the given files instead of `/proc/meminfo` and `/proc/sys/vm/drop_caches`
+    pub fn with_paths<P: Into<PathBuf>, Q: Into<PathBuf>>(meminfo: P, drop_caches: Q) -> Self {
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        debug!("System page size is {}", page_size);
+
+        Self {
+            page_size: if page_size > 0 {
+                page_size as usize
+            } else {
+                4096
+            },
+            meminfo_path: meminfo.into(),
+            drop_caches_path: drop_caches.into(),
+        }
+    }
+
+    pub fn meminfo(&self) -> crate::TimersetResult<MemInfo> {
+        Ok(std::fs::read_to_string(&self.meminfo_path)?.parse()?)
+    }
+}
+
+impl MemoryBackend for LinuxMemory {
+    fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    fn total_physical_memory(&mut self) -> crate::TimersetResult<usize> {
+        Ok(self.meminfo()?.mem_total as usize)
+    }
+
+    fn acquire_privileges(&mut self) -> crate::TimersetResult<()> {
+        // Writing to drop_caches only requires running as root, nothing to acquire
+        Ok(())
+    }
+
+    fn query_memory_lists(&mut self) -> crate::TimersetResult<MemoryListInfo> {
+        let meminfo = self.meminfo()?;
+        debug!("{:?}", meminfo);
+        Ok(meminfo.to_memory_lists(self.page_size))
+    }
+
+    fn purge(&mut self, command: PurgeCommand) -> crate::TimersetResult<()> {
+        if !self.supports(command) {
+            return Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into());
+        }
+
+        match command {
+            // Writing back dirty pages is what flushing the modified list amounts to
+            PurgeCommand::FlushModifiedList => unsafe { libc::sync() },
+            PurgeCommand::PurgeStandbyList => {
+                // drop_caches only drops clean pages, so write dirty ones back first.
+                // Writing 1 drops the page cache only, leaving dentries and inodes alone.
+                unsafe { libc::sync() };
+                std::fs::write(&self.drop_caches_path, b"1")?;
+                debug!("Wrote 1 to {:?}", self.drop_caches_path);
+            }
+            PurgeCommand::PurgeLowPriorityStandbyList | PurgeCommand::EmptyWorkingSets => {}
+        }
+        Ok(())
+    }
+
+    fn supports(&self, command: PurgeCommand) -> bool {
+        // The page cache can only be dropped as a whole, and there's no way to trim other processes
+        !matches!(
+            command,
+            PurgeCommand::PurgeLowPriorityStandbyList | PurgeCommand::EmptyWorkingSets
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LinuxMemory, MemInfo};
+    use crate::platform::{MemoryBackend as _, PurgeCommand};
+
+    const MEMINFO: &str = "\
+MemTotal:       16303412 kB
+MemFree:          981236 kB
+Buffers:          431120 kB
+Cached:          8650432 kB
+SwapCached:            0 kB
+Active:          6237408 kB
+Inactive:        7612716 kB
+Active(anon):      38244 kB
+Inactive(anon):  4927432 kB
+Active(file):    6199164 kB
+Inactive(file):  2685284 kB
+Dirty:              1024 kB
+HugePages_Total:       0
+Hugepagesize:       2048 kB
+";
+
+    #[test]
+    fn should_parse_meminfo() {
+        let meminfo: MemInfo = MEMINFO.parse().unwrap();
+        assert_eq!(
+            meminfo,
+            MemInfo {
+                mem_total: 16303412 * 1024,
+                mem_free: 981236 * 1024,
+                cached: 8650432 * 1024,
+                inactive_file: 2685284 * 1024,
+                dirty: 1024 * 1024,
+            }
+        );
+
+        assert!("MemTotal: lots kB".parse::<MemInfo>().is_err());
+        assert!("MemTotal 16303412 kB".parse::<MemInfo>().is_err());
+    }
+
+    #[test]
+    fn should_map_page_cache_to_standby_list() {
+        let lists = MEMINFO.parse::<MemInfo>().unwrap().to_memory_lists(4096);
+        assert_eq!(lists.free_pages() * 4096, 981236 * 1024);
+        assert_eq!(lists.standby_pages() * 4096, 8650432 * 1024);
+        assert_eq!(lists.page_count_by_priority[2] * 4096, 2685284 * 1024);
+        assert_eq!(lists.modified_page_count, 256);
+    }
+
+    #[test]
+    fn should_query_and_purge_through_proc_files() {
+        let dir = std::env::temp_dir().join(format!("timerset-proc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("meminfo"), MEMINFO).unwrap();
+        std::fs::write(dir.join("drop_caches"), b"").unwrap();
+
+        let mut memory = LinuxMemory::with_paths(dir.join("meminfo"), dir.join("drop_caches"));
+        let lists = memory.query_memory_lists().unwrap();
+        assert_eq!(
+            lists.standby_pages() * memory.page_size(),
+            8650432 * 1024 / memory.page_size() * memory.page_size()
+        );
+
+        assert_eq!(memory.total_physical_memory().unwrap(), 16303412 * 1024);
+
+        memory.purge(PurgeCommand::PurgeStandbyList).unwrap();
+        assert_eq!(std::fs::read(dir.join("drop_caches")).unwrap(), b"1");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,13 @@
+mod memory;
+mod notification;
+mod power;
+mod process;
+mod shutdown;
+mod timer;
+
+pub use self::memory::*;
+pub use self::notification::*;
+pub use self::power::*;
+pub use self::process::*;
+pub use self::shutdown::*;
+pub use self::timer::*;
@@ -0,0 +1,180 @@
+use std::io::Write as _;
+use std::os::unix::io::AsRawFd as _;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+
+use crate::platform::MemoryNotification;
+
+const PSI_MEMORY_PATH: &str = "/proc/pressure/memory";
+
+/// Memory pressure notifications through a Pressure Stall Information trigger.
+///
+/// PSI triggers fire when tasks were stalled on memory for more than `stall` within any `window`,
+/// which is the closest Linux equivalent to a LowMemoryResourceNotification. Free memory thresholds
+/// don't apply to them: the cleaner checks those itself once woken up.
+/// On kernels without PSI, `wait` returns right away and the cleaner ends up polling at its throttle pace.
+#[derive(Debug)]
+pub struct PsiNotification {
+    path: PathBuf,
+    stall: Duration,
+    window: Duration,
+    trigger: Option<std::fs::File>,
+}
+
+impl Default for PsiNotification {
+    fn default() -> Self {
+        Self::with_path(PSI_MEMORY_PATH)
+    }
+}
+
+impl PsiNotification {
+    /// Uses `path` instead of `/proc/pressure/memory`
+    pub fn with_path<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            stall: Duration::from_millis(150),
+            window: Duration::from_secs(1),
+            trigger: None,
+        }
+    }
+}
+
+impl MemoryNotification for PsiNotification {
+    fn arm(&mut self, _freemem_threshold: usize) -> crate::TimersetResult<()> {
+        // Triggers are written as "some <stall μs> <window μs>" and stay active as long as the fd is open
+        let trigger = format!(
+            "some {} {}",
+            self.stall.as_micros(),
+            self.window.as_micros()
+        );
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&self.path)
+            .and_then(|mut file| {
+                file.write_all(trigger.as_bytes())?;
+                Ok(file)
+            });
+
+        match file {
+            Ok(file) => {
+                debug!("Armed PSI trigger {:?} on {:?}", trigger, self.path);
+                self.trigger = Some(file);
+            }
+            Err(e) => warn!(
+                "Could not set up a PSI trigger on {:?}, falling back to polling: {}",
+                self.path, e
+            ),
+        }
+
+        Ok(())
+    }
+
+    fn wait(&mut self, timeout: Duration) -> crate::TimersetResult<bool> {
+        let file = match &self.trigger {
+            Some(file) => file,
+            None => return Ok(true),
+        };
+        // Retries only wait for what's left of the timeout
+        let deadline = Instant::now() + timeout;
+
+        let mut fds = libc::pollfd {
+            fd: file.as_raw_fd(),
+            events: libc::POLLPRI,
+            revents: 0,
+        };
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let timeout_ms = i32::try_from(remaining.as_millis()).unwrap_or(i32::MAX);
+            match unsafe { libc::poll(&mut fds, 1, timeout_ms) } {
+                0 => return Ok(false),
+                ret if ret < 0 => {
+                    let e = std::io::Error::last_os_error();
+                    if e.kind() == std::io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    return Err(e.into());
+                }
+                _ => {}
+            }
+
+            if fds.revents & (libc::POLLERR | libc::POLLHUP | libc::POLLNVAL) != 0 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::BrokenPipe,
+                    "PSI trigger is no longer available",
+                )
+                .into());
+            }
+
+            if fds.revents & libc::POLLPRI != 0 {
+                return Ok(true);
+            }
+        }
+    }
+
+    fn disarm(&mut self) -> crate::TimersetResult<()> {
+        if self.trigger.take().is_some() {
+            debug!("Closed PSI trigger");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::PsiNotification;
+    use crate::platform::MemoryNotification as _;
+
+    #[test]
+    fn should_fall_back_to_polling_without_psi() {
+        let mut notification = PsiNotification::with_path("/nonexistent/pressure/memory");
+        notification.arm(0).unwrap();
+        assert!(notification.trigger.is_none());
+        assert!(notification.wait(Duration::from_secs(1)).unwrap());
+        notification.disarm().unwrap();
+    }
+
+    /// Pipe standing in for a PSI trigger: nothing ever gets signaled on it, and closing the write end hangs it up
+    fn pipe() -> (std::fs::File, std::fs::File) {
+        use std::os::unix::io::FromRawFd as _;
+
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        unsafe {
+            (
+                std::fs::File::from_raw_fd(fds[0]),
+                std::fs::File::from_raw_fd(fds[1]),
+            )
+        }
+    }
+
+    #[test]
+    fn should_wait_no_longer_than_timeout() {
+        let (read, _write) = pipe();
+        let mut notification = PsiNotification {
+            trigger: Some(read),
+            ..Default::default()
+        };
+
+        let start = std::time::Instant::now();
+        assert!(!notification.wait(Duration::from_millis(50)).unwrap());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn should_fail_once_trigger_hangs_up() {
+        let (read, write) = pipe();
+        let mut notification = PsiNotification {
+            trigger: Some(read),
+            ..Default::default()
+        };
+        drop(write);
+
+        assert!(notification.wait(Duration::from_secs(5)).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use std::path::{Path, PathBuf};
+
+use crate::platform::{PowerSource, PowerState};
+
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+
+/// Power source, as reported by the power supplies in `/sys/class/power_supply`.
+/// The system is on battery only while none of its adapters is online and one of its batteries discharges.
+#[derive(Debug, Clone)]
+pub struct SysfsPowerSupply {
+    root: PathBuf,
+}
+
+impl Default for SysfsPowerSupply {
+    fn default() -> Self {
+        Self::with_root(POWER_SUPPLY_PATH)
+    }
+}
+
+impl SysfsPowerSupply {
+    /// Uses the given folder instead of `/sys/class/power_supply`
+    pub fn with_root<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+fn read_attribute(supply: &Path, name: &str) -> Option<String> {
+    std::fs::read_to_string(supply.join(name))
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+impl PowerSource for SysfsPowerSupply {
+    fn power_state(&mut self) -> crate::TimersetResult<PowerState> {
+        let mut discharging = false;
+        for entry in std::fs::read_dir(&self.root)? {
+            let supply = entry?.path();
+            // Batteries of mice, keyboards, gamepads... don't power the system
+            if read_attribute(&supply, "scope").as_deref() == Some("Device") {
+                continue;
+            }
+
+            match read_attribute(&supply, "type").as_deref() {
+                // USB-C chargers show up as USB supplies
+                Some("Mains" | "USB")
+                    if read_attribute(&supply, "online").as_deref() == Some("1") =>
+                {
+                    return Ok(PowerState::Ac);
+                }
+                Some("Battery") => {
+                    discharging |= read_attribute(&supply, "present").as_deref() != Some("0")
+                        && read_attribute(&supply, "status").as_deref() == Some("Discharging");
+                }
+                _ => {}
+            }
+        }
+
+        // Desktops have no battery, and some of them no adapter either
+        Ok(if discharging {
+            PowerState::Battery
+        } else {
+            PowerState::Ac
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SysfsPowerSupply;
+    use crate::platform::{PowerSource as _, PowerState};
+
+    fn supply(root: &std::path::Path, name: &str, attributes: &[(&str, &str)]) {
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (attribute, value) in attributes {
+            std::fs::write(dir.join(attribute), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn should_read_power_state_from_sysfs() {
+        let root = std::env::temp_dir().join(format!("timerset-power-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let mut power = SysfsPowerSupply::with_root(&root);
+
+        // Desktop
+        assert_eq!(power.power_state().unwrap(), PowerState::Ac);
+
+        supply(&root, "AC", &[("type", "Mains"), ("online", "1")]);
+        supply(
+            &root,
+            "BAT0",
+            &[("type", "Battery"), ("status", "Charging")],
+        );
+        assert_eq!(power.power_state().unwrap(), PowerState::Ac);
+
+        supply(&root, "AC", &[("online", "0")]);
+        supply(&root, "BAT0", &[("status", "Discharging")]);
+        assert_eq!(power.power_state().unwrap(), PowerState::Battery);
+
+        // Only a discharging battery counts, a full one doesn't
+        supply(&root, "BAT0", &[("status", "Full")]);
+        assert_eq!(power.power_state().unwrap(), PowerState::Ac);
+
+        // Removed battery
+        supply(
+            &root,
+            "BAT0",
+            &[("status", "Discharging"), ("present", "0")],
+        );
+        assert_eq!(power.power_state().unwrap(), PowerState::Ac);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn should_ignore_peripheral_batteries_and_offline_usb_supplies() {
+        let root =
+            std::env::temp_dir().join(format!("timerset-power-device-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let mut power = SysfsPowerSupply::with_root(&root);
+
+        // Desktop with an unplugged USB port showing up as a power supply
+        supply(
+            &root,
+            "ucsi-source-psy-USBC000:001",
+            &[("type", "USB"), ("online", "0")],
+        );
+        assert_eq!(power.power_state().unwrap(), PowerState::Ac);
+
+        // Wireless mouse running on its own battery
+        supply(
+            &root,
+            "hidpp_battery_0",
+            &[
+                ("type", "Battery"),
+                ("scope", "Device"),
+                ("status", "Discharging"),
+            ],
+        );
+        assert_eq!(power.power_state().unwrap(), PowerState::Ac);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+use std::path::{Path, PathBuf};
+
+use crate::platform::ProcessSource;
+
+const PROC_PATH: &str = "/proc";
+
+/// Running processes, as listed in `/proc`
+#[derive(Debug, Clone)]
+pub struct ProcfsProcesses {
+    root: PathBuf,
+}
+
+impl Default for ProcfsProcesses {
+    fn default() -> Self {
+        Self {
+            root: PROC_PATH.into(),
+        }
+    }
+}
+
+/// File name of a process' executable, from the first argument of its command line.
+/// Both separators are handled, since Wine and Proton processes keep their Windows path.
+fn executable_name(cmdline: &[u8]) -> Option<String> {
+    let argv0 = cmdline.split(|&b| b == 0).next()?;
+    let argv0 = String::from_utf8_lossy(argv0);
+    let name = argv0.rsplit(['/', '\\']).next()?.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+impl ProcessSource for ProcfsProcesses {
+    fn running_processes(&mut self) -> crate::TimersetResult<Vec<String>> {
+        let mut names = vec![];
+        for entry in std::fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            if !path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.bytes().all(|b| b.is_ascii_digit()))
+            {
+                continue;
+            }
+
+            // Processes can exit while being listed, and kernel threads have no command line:
+            // those fall back to `comm`, which is truncated to 15 characters
+            let name = std::fs::read(path.join("cmdline"))
+                .ok()
+                .and_then(|cmdline| executable_name(&cmdline))
+                .or_else(|| {
+                    std::fs::read_to_string(path.join("comm"))
+                        .ok()
+                        .map(|comm| comm.trim_end().to_string())
+                });
+            names.extend(name);
+        }
+        Ok(names)
+    }
+}
+
+/// Start time of a running process, in clock ticks since boot; `None` if it isn't running.
+/// Tells a process apart from a later one that got the same ID.
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    let stat =
+        std::fs::read_to_string(Path::new(PROC_PATH).join(pid.to_string()).join("stat")).ok()?;
+    // The command name can hold spaces and parentheses: fields are counted from its closing parenthesis,
+    // `starttime` being the 22nd one and the state the 3rd
+    stat.rsplit_once(')')?
+        .1
+        .split_whitespace()
+        .nth(22 - 3)?
+        .parse()
+        .ok()
+}
+
+/// Process IDs of the other running timerset instances, matched on the process name
+pub fn running_instances() -> crate::TimersetResult<Vec<u32>> {
+    let root = std::path::Path::new(PROC_PATH);
+    let own_name = std::fs::read_to_string(root.join("self").join("comm"))?;
+    let own_pid = std::process::id();
+
+    let mut pids = vec![];
+    for entry in std::fs::read_dir(root)? {
+        let path = entry?.path();
+        let Some(pid) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.parse::<u32>().ok())
+        else {
+            continue;
+        };
+
+        if pid != own_pid
+            && std::fs::read_to_string(path.join("comm")).is_ok_and(|name| name == own_name)
+        {
+            pids.push(pid);
+        }
+    }
+    Ok(pids)
+}
+
+#[cfg(test)]
+mod test {
+    use super::executable_name;
+
+    #[test]
+    fn should_extract_executable_names() {
+        assert_eq!(
+            executable_name(b"/usr/bin/steam\0-silent\0").as_deref(),
+            Some("steam")
+        );
+        assert_eq!(
+            executable_name(b"Z:\\Games\\Game\\Game-Win64-Shipping.exe\0").as_deref(),
+            Some("Game-Win64-Shipping.exe")
+        );
+        assert_eq!(executable_name(b""), None);
+    }
+}
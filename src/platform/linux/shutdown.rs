@@ -0,0 +1,56 @@
+use log::{debug, warn};
+
+use crate::shutdown::{Shutdown, ShutdownReason};
+
+/// Requests a shutdown on SIGINT, SIGTERM and SIGHUP.
+///
+/// Signals are blocked and picked up by a dedicated thread through `sigwait`, which keeps signal
+/// handler restrictions out of the way. Blocked signals are inherited by threads spawned afterwards,
+/// so this has to be called before any other thread gets spawned.
+/// A second signal received while shutting down exits right away.
+pub fn install_shutdown_handler(shutdown: Shutdown) -> crate::TimersetResult<()> {
+    let mut set: libc::sigset_t = unsafe { std::mem::zeroed() };
+    unsafe {
+        libc::sigemptyset(&mut set);
+        libc::sigaddset(&mut set, libc::SIGINT);
+        libc::sigaddset(&mut set, libc::SIGTERM);
+        libc::sigaddset(&mut set, libc::SIGHUP);
+    }
+
+    let ret = unsafe { libc::pthread_sigmask(libc::SIG_BLOCK, &set, std::ptr::null_mut()) };
+    if ret != 0 {
+        return Err(std::io::Error::from_raw_os_error(ret).into());
+    }
+
+    std::thread::spawn(move || loop {
+        let mut signal = 0;
+        if unsafe { libc::sigwait(&set, &mut signal) } != 0 {
+            continue;
+        }
+
+        if shutdown.requested().is_some() {
+            warn!(
+                "Received signal {} while shutting down, exiting now",
+                signal
+            );
+            std::process::exit(128 + signal);
+        }
+
+        debug!("Received signal {}", signal);
+        shutdown.request(match signal {
+            libc::SIGINT => ShutdownReason::Interrupt,
+            libc::SIGHUP => ShutdownReason::Logoff,
+            _ => ShutdownReason::Terminate,
+        });
+    });
+
+    Ok(())
+}
+
+/// Asks another timerset instance to shut down through SIGTERM
+pub fn stop_instance(pid: u32) -> crate::TimersetResult<()> {
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use log::{debug, warn};
+
+use crate::platform::{TimerBackend, TimerRange};
+use crate::timer::TimerResolution;
+
+const CPU_DMA_LATENCY_PATH: &str = "/dev/cpu_dma_latency";
+
+/// Finest wakeup latency requested through PM QoS: 0μs would keep CPUs out of every idle state
+const MIN_WAKEUP_LATENCY_US: u32 = 1;
+
+/// Rounds nanoseconds up to the closest 100-nanosecond unit
+fn ns_to_resolution(ns: u64) -> TimerResolution {
+    TimerResolution::from_hns(ns.div_ceil(100).clamp(1, u32::MAX as u64) as u32)
+}
+
+fn timer_slack() -> crate::TimersetResult<TimerResolution> {
+    let slack = unsafe { libc::prctl(libc::PR_GET_TIMERSLACK, 0, 0, 0, 0) };
+    if slack < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(ns_to_resolution(slack as u64))
+}
+
+/// Closest Linux equivalent of the NT Kernel timer resolution, which it is not equivalent to.
+///
+/// Linux timers are high resolution already, but wakeups get delayed by up to the thread's timer slack
+/// (50μs by default) so that they can be coalesced, and CPUs in deep C-states take a while to wake up.
+/// Setting a resolution thus lowers the timer slack through `prctl(PR_SET_TIMERSLACK)` and requests
+/// a wakeup latency of the same period, rounded up to at least 1μs, through the `/dev/cpu_dma_latency`
+/// PM QoS interface. Just like `NtSetTimerResolution`, the PM QoS request is held as long as this backend lives.
+///
+/// Only the PM QoS request affects the rest of the system. The timer slack is a per-thread setting,
+/// inherited by threads spawned afterwards: it changes nothing for other processes, and the current
+/// resolution reported is the slack of the calling thread.
+///
+/// Without a requested value, 10μs is used: the finest value (the clock resolution, usually 1ns)
+/// would keep CPUs out of every idle state for no measurable gain.
+#[derive(Debug)]
+pub struct LinuxTimer {
+    default_slack: TimerResolution,
+    pm_qos_path: PathBuf,
+    pm_qos: Option<std::fs::File>,
+    /// Whether failing to open `pm_qos_path` was reported already
+    pm_qos_warned: bool,
+}
+
+impl Default for LinuxTimer {
+    fn default() -> Self {
+        Self::with_pm_qos_path(CPU_DMA_LATENCY_PATH)
+    }
+}
+
+impl LinuxTimer {
+    /// Uses `path` instead of `/dev/cpu_dma_latency` for PM QoS requests
+    pub fn with_pm_qos_path<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            // The slack the process started with is the coarsest value, there is no reason to go higher
+            default_slack: timer_slack().unwrap_or(TimerResolution::from_micros(50)),
+            pm_qos_path: path.into(),
+            pm_qos: None,
+            pm_qos_warned: false,
+        }
+    }
+
+    fn request_latency(&mut self, value: TimerResolution) -> crate::TimersetResult<()> {
+        // Requests are written as a native s32 in μs, and hold as long as the file stays open
+        let latency = value
+            .as_hns()
+            .div_ceil(10)
+            .clamp(MIN_WAKEUP_LATENCY_US, i32::MAX as u32) as i32;
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&self.pm_qos_path)?;
+        file.write_all(&latency.to_ne_bytes())?;
+        debug!(
+            "Requested {}μs wakeup latency through {:?}",
+            latency, self.pm_qos_path
+        );
+        self.pm_qos = Some(file);
+        Ok(())
+    }
+}
+
+impl TimerBackend for LinuxTimer {
+    const SYSTEM_WIDE: bool = false;
+    const DEFAULT_RESOLUTION: Option<TimerResolution> = Some(TimerResolution::from_micros(10));
+    const DISCRETE_STEPS: bool = false;
+
+    fn query_resolution(&self) -> crate::TimersetResult<TimerRange> {
+        let mut res = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        if unsafe { libc::clock_getres(libc::CLOCK_MONOTONIC, &mut res) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        Ok(TimerRange {
+            min: self.default_slack,
+            max: ns_to_resolution(res.tv_sec as u64 * 1_000_000_000 + res.tv_nsec as u64),
+            cur: timer_slack()?,
+        })
+    }
+
+    fn set_resolution(&mut self, value: TimerResolution) -> crate::TimersetResult<TimerResolution> {
+        let slack_ns = value.as_hns() as libc::c_ulong * 100;
+        if unsafe { libc::prctl(libc::PR_SET_TIMERSLACK, slack_ns, 0, 0, 0) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        // Holding a PM QoS request needs root; the timer slack alone still helps timerset's own threads
+        if let Err(e) = self.request_latency(value) {
+            if !std::mem::replace(&mut self.pm_qos_warned, true) {
+                warn!(
+                    "Could not request a wakeup latency through {:?}: {}",
+                    self.pm_qos_path, e
+                );
+            } else {
+                debug!(
+                    "Could not request a wakeup latency through {:?}: {}",
+                    self.pm_qos_path, e
+                );
+            }
+        }
+
+        timer_slack()
+    }
+
+    fn release_resolution(
+        &mut self,
+        _value: TimerResolution,
+    ) -> crate::TimersetResult<TimerResolution> {
+        let slack_ns = self.default_slack.as_hns() as libc::c_ulong * 100;
+        if unsafe { libc::prctl(libc::PR_SET_TIMERSLACK, slack_ns, 0, 0, 0) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        if self.pm_qos.take().is_some() {
+            debug!("Released wakeup latency request on {:?}", self.pm_qos_path);
+        }
+
+        timer_slack()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LinuxTimer;
+    use crate::platform::TimerBackend as _;
+    use crate::timer::TimerResolution;
+
+    #[test]
+    fn should_set_timer_slack_and_hold_pm_qos_request() {
+        let pm_qos_path =
+            std::env::temp_dir().join(format!("timerset-cpu_dma_latency-{}", std::process::id()));
+        std::fs::write(&pm_qos_path, b"").unwrap();
+
+        // Timer slack is per-thread, so this doesn't leak into other tests
+        std::thread::spawn(move || {
+            let mut timer = LinuxTimer::with_pm_qos_path(&pm_qos_path);
+            let range = timer.query_resolution().unwrap();
+            assert!(range.max <= range.min);
+
+            let cur = timer
+                .set_resolution(TimerResolution::from_micros(20))
+                .unwrap();
+            assert_eq!(cur, TimerResolution::from_micros(20));
+            assert_eq!(timer.query_resolution().unwrap().cur, cur);
+            assert!(timer.pm_qos.is_some());
+            assert_eq!(
+                std::fs::read(&pm_qos_path).unwrap(),
+                20i32.to_ne_bytes().to_vec()
+            );
+
+            // Never a 0μs latency, which would disable every CPU idle state
+            timer.set_resolution(TimerResolution::from_hns(1)).unwrap();
+            assert_eq!(
+                std::fs::read(&pm_qos_path).unwrap(),
+                1i32.to_ne_bytes().to_vec()
+            );
+            timer.set_resolution(TimerResolution::from_hns(25)).unwrap();
+            assert_eq!(
+                std::fs::read(&pm_qos_path).unwrap(),
+                3i32.to_ne_bytes().to_vec()
+            );
+
+            std::fs::remove_file(&pm_qos_path).unwrap();
+        })
+        .join()
+        .unwrap();
+    }
+}
@@ -0,0 +1,211 @@
+//! Platform abstraction layer.
+//!
+//! Every interaction timerset has with the operating system goes through one of the traits below,
+//! so that the decision logic (clamping, thresholds, install steps...) stays platform-neutral and
+//! can be exercised against the in-memory simulated backends in tests.
+
+use std::path::{Path, PathBuf};
+
+use crate::timer::TimerResolution;
+use crate::utils::StartArgs;
+
+#[cfg(windows)]
+mod windows;
+#[cfg(windows)]
+pub use self::windows::{
+    install_shutdown_handler, is_elevated, os_build, process_start_time, registry_journal_path,
+    running_instances, stop_instance, CmrnNotification as NativeNotification,
+    NtMemory as NativeMemory, NtTimer as NativeTimer, SystemPowerStatus as NativePower,
+    TaskSchedulerInstaller as NativeInstaller, ToolhelpProcesses as NativeProcesses,
+    WinRegistry as NativeRegistry,
+};
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+pub use self::linux::{
+    install_shutdown_handler, process_start_time, running_instances, stop_instance,
+    LinuxMemory as NativeMemory, LinuxTimer as NativeTimer, ProcfsProcesses as NativeProcesses,
+    PsiNotification as NativeNotification, SysfsPowerSupply as NativePower,
+};
+
+#[cfg(not(windows))]
+mod unsupported;
+#[cfg(not(any(windows, target_os = "linux")))]
+pub use self::unsupported::{
+    install_shutdown_handler, process_start_time, running_instances, stop_instance,
+    UnsupportedMemory as NativeMemory, UnsupportedNotification as NativeNotification,
+    UnsupportedPower as NativePower, UnsupportedProcesses as NativeProcesses,
+    UnsupportedTimer as NativeTimer,
+};
+#[cfg(not(windows))]
+pub use self::unsupported::{
+    is_elevated, os_build, registry_journal_path, NullRegistry as NativeRegistry,
+    UnsupportedInstaller as NativeInstaller,
+};
+
+mod journal;
+pub use self::journal::*;
+
+mod poll;
+pub use self::poll::*;
+
+#[cfg(test)]
+pub mod sim;
+
+/// Timer resolution values as reported by the kernel
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct TimerRange {
+    pub min: TimerResolution,
+    pub max: TimerResolution,
+    pub cur: TimerResolution,
+}
+
+/// Kernel timer resolution operations
+pub trait TimerBackend {
+    /// Whether a resolution applies to the whole system, rather than to the calling thread only
+    const SYSTEM_WIDE: bool = true;
+    /// Resolution requested when none is given, the finest one if `None`
+    const DEFAULT_RESOLUTION: Option<TimerResolution> = None;
+    /// Whether the kernel only grants a handful of resolutions, rounding requests to one of them
+    const DISCRETE_STEPS: bool = true;
+
+    /// Queries the minimum, maximum and current timer resolutions
+    fn query_resolution(&self) -> crate::TimersetResult<TimerRange>;
+    /// Requests the given timer resolution and returns the resulting current resolution
+    fn set_resolution(&mut self, value: TimerResolution) -> crate::TimersetResult<TimerResolution>;
+    /// Withdraws a request made through `set_resolution` and returns the resulting current resolution
+    fn release_resolution(
+        &mut self,
+        value: TimerResolution,
+    ) -> crate::TimersetResult<TimerResolution>;
+}
+
+/// Platform-neutral snapshot of the system memory lists, in pages
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct MemoryListInfo {
+    pub zero_page_count: usize,
+    pub free_page_count: usize,
+    pub modified_page_count: usize,
+    pub bad_page_count: usize,
+    pub page_count_by_priority: [usize; 8],
+    pub repurposed_pages_by_priority: [usize; 8],
+    pub modified_page_count_page_file: usize,
+}
+
+impl MemoryListInfo {
+    /// Undocumented: StandbyList size is calculated by summing all the page count per priority
+    pub fn standby_pages(&self) -> usize {
+        self.page_count_by_priority.iter().sum()
+    }
+
+    /// Undocumented: Free memory is the sum of zeroed AND free pages
+    /// Free memory here is actual free, zeroed, non-repurposed physical memory
+    pub fn free_pages(&self) -> usize {
+        self.zero_page_count + self.free_page_count
+    }
+}
+
+/// Memory list commands, mirroring the NT Kernel's `SYSTEM_MEMORY_LIST_COMMAND`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PurgeCommand {
+    /// Trims the working sets of all processes, moving their pages to the standby/modified lists
+    EmptyWorkingSets,
+    /// Writes the modified list back to disk, moving its pages to the standby list
+    FlushModifiedList,
+    /// Purges the whole standby list
+    PurgeStandbyList,
+    /// Purges the priority 0 part of the standby list only
+    PurgeLowPriorityStandbyList,
+}
+
+/// Memory list query/purge operations
+pub trait MemoryBackend {
+    /// Size of a memory page in bytes
+    fn page_size(&self) -> usize;
+    /// Total physical memory in bytes
+    fn total_physical_memory(&mut self) -> crate::TimersetResult<usize>;
+    /// Acquires whatever privileges are needed to purge the memory lists
+    fn acquire_privileges(&mut self) -> crate::TimersetResult<()>;
+    /// Retrieves the current state of the memory lists
+    fn query_memory_lists(&mut self) -> crate::TimersetResult<MemoryListInfo>;
+    /// Runs a memory list command, usually reclaiming cached memory as free.
+    /// Fails with `ErrorKind::Unsupported` for commands the platform has no equivalent for.
+    fn purge(&mut self, command: PurgeCommand) -> crate::TimersetResult<()>;
+    /// Whether the platform has an equivalent for the command, i.e. `purge` doesn't fail with `ErrorKind::Unsupported`
+    fn supports(&self, _command: PurgeCommand) -> bool {
+        true
+    }
+}
+
+/// Source of memory pressure notifications the standby list cleaner waits on
+pub trait MemoryNotification {
+    /// Sets up the notification; `freemem_threshold` is the free memory threshold in bytes,
+    /// for sources that can be configured with one
+    fn arm(&mut self, freemem_threshold: usize) -> crate::TimersetResult<()>;
+    /// Blocks until the system signals memory pressure, or `timeout` elapsed.
+    /// Returns whether memory pressure was signaled.
+    fn wait(&mut self, timeout: std::time::Duration) -> crate::TimersetResult<bool>;
+    /// Tears down the notification and whatever system state `arm` changed
+    fn disarm(&mut self) -> crate::TimersetResult<()>;
+}
+
+/// Listing of the processes running on the system
+pub trait ProcessSource {
+    /// Executable file names (e.g. `game.exe`) of the running processes
+    fn running_processes(&mut self) -> crate::TimersetResult<Vec<String>>;
+}
+
+/// Where the system draws its power from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PowerState {
+    /// Plugged in, or a system without battery
+    #[default]
+    Ac,
+    Battery,
+}
+
+impl std::fmt::Display for PowerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Ac => "AC power",
+            Self::Battery => "battery",
+        })
+    }
+}
+
+/// Power source status
+pub trait PowerSource {
+    fn power_state(&mut self) -> crate::TimersetResult<PowerState>;
+}
+
+/// Path of the Memory Management key, relative to HKEY_LOCAL_MACHINE
+#[cfg_attr(not(windows), allow(dead_code))]
+pub const MEMORY_MANAGEMENT_KEY: &str =
+    "System\\CurrentControlSet\\Control\\Session Manager\\Memory Management";
+
+/// Path of the kernel key, relative to HKEY_LOCAL_MACHINE
+#[cfg_attr(not(windows), allow(dead_code))]
+pub const KERNEL_KEY: &str = "System\\CurrentControlSet\\Control\\Session Manager\\kernel";
+
+/// Access to machine-wide (HKEY_LOCAL_MACHINE) registry values
+#[cfg_attr(not(windows), allow(dead_code))]
+pub trait RegistryBackend {
+    fn get_u32(&self, path: &str, name: &str) -> crate::TimersetResult<Option<u32>>;
+    fn set_u32(&mut self, path: &str, name: &str, value: u32) -> crate::TimersetResult<()>;
+    fn delete_value(&mut self, path: &str, name: &str) -> crate::TimersetResult<()>;
+}
+
+/// Installation of timerset as a task running at startup
+pub trait TaskInstaller {
+    /// Folder where the executable gets installed
+    fn install_dir(&self) -> crate::TimersetResult<PathBuf>;
+    /// Copies the running executable to `dest`, creating parent folders as needed
+    fn copy_executable(&mut self, dest: &Path) -> crate::TimersetResult<()>;
+    /// Removes the installation folder and its contents
+    fn remove_install_dir(&mut self, dir: &Path) -> crate::TimersetResult<()>;
+    /// Registers (or replaces) the startup task named `name`
+    fn register_startup_task(&mut self, name: &str, args: &StartArgs) -> crate::TimersetResult<()>;
+    /// Deletes the startup task named `name`
+    fn delete_startup_task(&mut self, name: &str) -> crate::TimersetResult<()>;
+}
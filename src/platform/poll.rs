@@ -0,0 +1,21 @@
+use crate::platform::MemoryNotification;
+
+/// Notification source that never waits: the standby list cleaner then wakes up at its
+/// throttle pace, and samples the memory lists on a fixed interval.
+/// Nothing is set up on the system, unlike CMRN which needs the `LowMemoryThreshold` registry value.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PollingNotification;
+
+impl MemoryNotification for PollingNotification {
+    fn arm(&mut self, _freemem_threshold: usize) -> crate::TimersetResult<()> {
+        Ok(())
+    }
+
+    fn wait(&mut self, _timeout: std::time::Duration) -> crate::TimersetResult<bool> {
+        Ok(true)
+    }
+
+    fn disarm(&mut self) -> crate::TimersetResult<()> {
+        Ok(())
+    }
+}
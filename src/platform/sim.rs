@@ -0,0 +1,309 @@
+//! In-memory simulated backends, driven by tests.
+//!
+//! Every backend is a cheap handle over shared state: keep a clone around to inspect or tweak
+//! what the code under test sees after handing the backend over.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::platform::{
+    MemoryBackend, MemoryListInfo, MemoryNotification, PowerSource, PowerState, ProcessSource,
+    PurgeCommand, RegistryBackend, TaskInstaller, TimerBackend, TimerRange,
+};
+use crate::timer::TimerResolution;
+use crate::utils::StartArgs;
+
+#[derive(Debug, Default)]
+pub struct SimTimerState {
+    pub range: TimerRange,
+    /// Every value passed to `set_resolution`, in order
+    pub requests: Vec<TimerResolution>,
+    /// Every value passed to `release_resolution`, in order
+    pub releases: Vec<TimerResolution>,
+    /// Resolutions the simulated kernel grants, finest first. Requests get the coarsest of them
+    /// that is at least as fine as the requested value; any value is granted as is when empty.
+    pub achievable: Vec<TimerResolution>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimTimer(Arc<Mutex<SimTimerState>>);
+
+impl SimTimer {
+    /// Creates a simulated timer from raw 100-nanosecond values
+    pub fn new(min: u32, max: u32, cur: u32) -> Self {
+        Self(Arc::new(Mutex::new(SimTimerState {
+            range: TimerRange {
+                min: TimerResolution::from_hns(min),
+                max: TimerResolution::from_hns(max),
+                cur: TimerResolution::from_hns(cur),
+            },
+            ..Default::default()
+        })))
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, SimTimerState> {
+        self.0.lock().unwrap()
+    }
+}
+
+impl TimerBackend for SimTimer {
+    fn query_resolution(&self) -> crate::TimersetResult<TimerRange> {
+        Ok(self.state().range)
+    }
+
+    fn set_resolution(&mut self, value: TimerResolution) -> crate::TimersetResult<TimerResolution> {
+        let mut state = self.state();
+        state.requests.push(value);
+        let granted = match state
+            .achievable
+            .iter()
+            .rev()
+            .find(|&&granted| granted <= value)
+        {
+            Some(&granted) => granted,
+            None => state.achievable.first().copied().unwrap_or(value),
+        };
+        state.range.cur = granted;
+        Ok(granted)
+    }
+
+    fn release_resolution(
+        &mut self,
+        value: TimerResolution,
+    ) -> crate::TimersetResult<TimerResolution> {
+        let mut state = self.state();
+        state.releases.push(value);
+        state.range.cur = state.range.min;
+        Ok(state.range.min)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SimMemoryState {
+    pub page_size: usize,
+    pub total: usize,
+    pub lists: MemoryListInfo,
+    pub privileges_acquired: bool,
+    /// Every command passed to `purge`, in order
+    pub purges: Vec<PurgeCommand>,
+    /// Commands `purge` fails on with `ErrorKind::Unsupported`
+    pub unsupported: Vec<PurgeCommand>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimMemory(Arc<Mutex<SimMemoryState>>);
+
+impl SimMemory {
+    /// Creates a simulated system with 16GiB of RAM in 4KB pages, and the given free and standby sizes in bytes
+    pub fn new(free: usize, standby: usize) -> Self {
+        let sim = Self(Arc::new(Mutex::new(SimMemoryState {
+            page_size: 4096,
+            total: 16 << 30,
+            ..Default::default()
+        })));
+        sim.set_memory(free, standby);
+        sim
+    }
+
+    /// Replaces the memory lists with the given free and standby sizes, in bytes.
+    /// Standby pages are all put in the lowest priority.
+    pub fn set_memory(&self, free: usize, standby: usize) {
+        let mut state = self.state();
+        let page_size = state.page_size;
+        state.lists = MemoryListInfo {
+            free_page_count: free / page_size,
+            ..Default::default()
+        };
+        state.lists.page_count_by_priority[0] = standby / page_size;
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, SimMemoryState> {
+        self.0.lock().unwrap()
+    }
+}
+
+impl MemoryBackend for SimMemory {
+    fn page_size(&self) -> usize {
+        self.state().page_size
+    }
+
+    fn total_physical_memory(&mut self) -> crate::TimersetResult<usize> {
+        Ok(self.state().total)
+    }
+
+    fn acquire_privileges(&mut self) -> crate::TimersetResult<()> {
+        self.state().privileges_acquired = true;
+        Ok(())
+    }
+
+    fn query_memory_lists(&mut self) -> crate::TimersetResult<MemoryListInfo> {
+        Ok(self.state().lists)
+    }
+
+    fn purge(&mut self, command: PurgeCommand) -> crate::TimersetResult<()> {
+        if !self.supports(command) {
+            return Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into());
+        }
+        let mut state = self.state();
+        state.purges.push(command);
+        command.simulate(&mut state.lists);
+        Ok(())
+    }
+
+    fn supports(&self, command: PurgeCommand) -> bool {
+        !self.state().unsupported.contains(&command)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SimNotificationState {
+    /// Free memory threshold the notification is armed with, if any
+    pub armed: Option<usize>,
+    pub waits: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimNotification(Arc<Mutex<SimNotificationState>>);
+
+impl SimNotification {
+    pub fn state(&self) -> MutexGuard<'_, SimNotificationState> {
+        self.0.lock().unwrap()
+    }
+}
+
+impl MemoryNotification for SimNotification {
+    fn arm(&mut self, freemem_threshold: usize) -> crate::TimersetResult<()> {
+        self.state().armed = Some(freemem_threshold);
+        Ok(())
+    }
+
+    fn wait(&mut self, _timeout: std::time::Duration) -> crate::TimersetResult<bool> {
+        self.state().waits += 1;
+        Ok(true)
+    }
+
+    fn disarm(&mut self) -> crate::TimersetResult<()> {
+        self.state().armed = None;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimProcesses(Arc<Mutex<Vec<String>>>);
+
+impl SimProcesses {
+    /// Replaces the running processes with the given executable names
+    pub fn set_running(&self, names: &[&str]) {
+        *self.0.lock().unwrap() = names.iter().map(|&name| name.into()).collect();
+    }
+}
+
+impl ProcessSource for SimProcesses {
+    fn running_processes(&mut self) -> crate::TimersetResult<Vec<String>> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+}
+
+/// Power source reporting the last state set, or failing to be read when there is none
+#[derive(Debug, Clone)]
+pub struct SimPower(Arc<Mutex<Option<PowerState>>>);
+
+impl Default for SimPower {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(Some(PowerState::default()))))
+    }
+}
+
+impl SimPower {
+    pub fn set(&self, state: PowerState) {
+        *self.0.lock().unwrap() = Some(state);
+    }
+
+    /// Makes reading the power source fail until the next `set`
+    pub fn fail(&self) {
+        *self.0.lock().unwrap() = None;
+    }
+}
+
+impl PowerSource for SimPower {
+    fn power_state(&mut self) -> crate::TimersetResult<PowerState> {
+        self.0
+            .lock()
+            .unwrap()
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound).into())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimRegistry(Arc<Mutex<HashMap<(String, String), u32>>>);
+
+impl SimRegistry {
+    pub fn values(&self) -> MutexGuard<'_, HashMap<(String, String), u32>> {
+        self.0.lock().unwrap()
+    }
+
+    pub fn value(&self, path: &str, name: &str) -> Option<u32> {
+        self.values().get(&(path.into(), name.into())).copied()
+    }
+}
+
+impl RegistryBackend for SimRegistry {
+    fn get_u32(&self, path: &str, name: &str) -> crate::TimersetResult<Option<u32>> {
+        Ok(self.value(path, name))
+    }
+
+    fn set_u32(&mut self, path: &str, name: &str, value: u32) -> crate::TimersetResult<()> {
+        self.values().insert((path.into(), name.into()), value);
+        Ok(())
+    }
+
+    fn delete_value(&mut self, path: &str, name: &str) -> crate::TimersetResult<()> {
+        self.values()
+            .remove(&(path.into(), name.into()))
+            .map(|_| ())
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound).into())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SimInstallerState {
+    pub copied_to: Vec<PathBuf>,
+    pub removed_dirs: Vec<PathBuf>,
+    pub tasks: HashMap<String, StartArgs>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimInstaller(Arc<Mutex<SimInstallerState>>);
+
+impl SimInstaller {
+    pub fn state(&self) -> MutexGuard<'_, SimInstallerState> {
+        self.0.lock().unwrap()
+    }
+}
+
+impl TaskInstaller for SimInstaller {
+    fn install_dir(&self) -> crate::TimersetResult<PathBuf> {
+        Ok(PathBuf::from("C:\\Program Files\\TimerSet"))
+    }
+
+    fn copy_executable(&mut self, dest: &Path) -> crate::TimersetResult<()> {
+        self.state().copied_to.push(dest.into());
+        Ok(())
+    }
+
+    fn remove_install_dir(&mut self, dir: &Path) -> crate::TimersetResult<()> {
+        self.state().removed_dirs.push(dir.into());
+        Ok(())
+    }
+
+    fn register_startup_task(&mut self, name: &str, args: &StartArgs) -> crate::TimersetResult<()> {
+        self.state().tasks.insert(name.into(), args.clone());
+        Ok(())
+    }
+
+    fn delete_startup_task(&mut self, name: &str) -> crate::TimersetResult<()> {
+        self.state().tasks.remove(name);
+        Ok(())
+    }
+}
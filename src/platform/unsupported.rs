@@ -0,0 +1,192 @@
+//! Fallback backends for platforms timerset has no native implementation for.
+//! Every operation fails with `ErrorKind::Unsupported`.
+
+use std::path::{Path, PathBuf};
+
+use crate::platform::TaskInstaller;
+use crate::utils::StartArgs;
+
+fn unsupported<T>() -> crate::TimersetResult<T> {
+    Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into())
+}
+
+/// Returns true if the current process runs as root
+pub fn is_elevated() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// `Some(0)` while a process with the given ID runs: start times aren't available, so reused IDs can't be told apart
+#[cfg(not(target_os = "linux"))]
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    let pid = libc::pid_t::try_from(pid).ok().filter(|&pid| pid > 0)?;
+    // Signal 0 only checks whether the process exists; EPERM means it does, under another user
+    (unsafe { libc::kill(pid, 0) } == 0
+        || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM))
+    .then_some(0)
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnsupportedTimer;
+
+#[cfg(not(target_os = "linux"))]
+impl crate::platform::TimerBackend for UnsupportedTimer {
+    fn query_resolution(&self) -> crate::TimersetResult<crate::platform::TimerRange> {
+        unsupported()
+    }
+
+    fn set_resolution(
+        &mut self,
+        _value: crate::timer::TimerResolution,
+    ) -> crate::TimersetResult<crate::timer::TimerResolution> {
+        unsupported()
+    }
+
+    fn release_resolution(
+        &mut self,
+        _value: crate::timer::TimerResolution,
+    ) -> crate::TimersetResult<crate::timer::TimerResolution> {
+        unsupported()
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnsupportedMemory;
+
+#[cfg(not(target_os = "linux"))]
+impl crate::platform::MemoryBackend for UnsupportedMemory {
+    fn page_size(&self) -> usize {
+        4096
+    }
+
+    fn total_physical_memory(&mut self) -> crate::TimersetResult<usize> {
+        unsupported()
+    }
+
+    fn acquire_privileges(&mut self) -> crate::TimersetResult<()> {
+        unsupported()
+    }
+
+    fn query_memory_lists(&mut self) -> crate::TimersetResult<crate::platform::MemoryListInfo> {
+        unsupported()
+    }
+
+    fn purge(&mut self, _command: crate::platform::PurgeCommand) -> crate::TimersetResult<()> {
+        unsupported()
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnsupportedNotification;
+
+#[cfg(not(target_os = "linux"))]
+impl crate::platform::MemoryNotification for UnsupportedNotification {
+    fn arm(&mut self, _freemem_threshold: usize) -> crate::TimersetResult<()> {
+        unsupported()
+    }
+
+    fn wait(&mut self, _timeout: std::time::Duration) -> crate::TimersetResult<bool> {
+        unsupported()
+    }
+
+    fn disarm(&mut self) -> crate::TimersetResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnsupportedProcesses;
+
+#[cfg(not(target_os = "linux"))]
+impl crate::platform::ProcessSource for UnsupportedProcesses {
+    fn running_processes(&mut self) -> crate::TimersetResult<Vec<String>> {
+        unsupported()
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnsupportedPower;
+
+#[cfg(not(target_os = "linux"))]
+impl crate::platform::PowerSource for UnsupportedPower {
+    fn power_state(&mut self) -> crate::TimersetResult<crate::platform::PowerState> {
+        unsupported()
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn running_instances() -> crate::TimersetResult<Vec<u32>> {
+    unsupported()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn stop_instance(_pid: u32) -> crate::TimersetResult<()> {
+    unsupported()
+}
+
+/// There is no registry outside of Windows: nothing is ever set, so there is never anything to restore
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullRegistry;
+
+impl crate::platform::RegistryBackend for NullRegistry {
+    fn get_u32(&self, _path: &str, _name: &str) -> crate::TimersetResult<Option<u32>> {
+        Ok(None)
+    }
+
+    fn set_u32(&mut self, _path: &str, _name: &str, _value: u32) -> crate::TimersetResult<()> {
+        unsupported()
+    }
+
+    fn delete_value(&mut self, _path: &str, _name: &str) -> crate::TimersetResult<()> {
+        unsupported()
+    }
+}
+
+/// Windows build number, which no other platform has
+pub fn os_build() -> Option<u32> {
+    None
+}
+
+/// Location of the registry journal
+pub fn registry_journal_path() -> PathBuf {
+    "/var/lib/timerset/registry.journal".into()
+}
+
+/// Nothing to catch: the process just dies on signals, as it always did
+#[cfg(not(target_os = "linux"))]
+pub fn install_shutdown_handler(_shutdown: crate::shutdown::Shutdown) -> crate::TimersetResult<()> {
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnsupportedInstaller;
+
+impl TaskInstaller for UnsupportedInstaller {
+    fn install_dir(&self) -> crate::TimersetResult<PathBuf> {
+        unsupported()
+    }
+
+    fn copy_executable(&mut self, _dest: &Path) -> crate::TimersetResult<()> {
+        unsupported()
+    }
+
+    fn remove_install_dir(&mut self, _dir: &Path) -> crate::TimersetResult<()> {
+        unsupported()
+    }
+
+    fn register_startup_task(
+        &mut self,
+        _name: &str,
+        _args: &StartArgs,
+    ) -> crate::TimersetResult<()> {
+        unsupported()
+    }
+
+    fn delete_startup_task(&mut self, _name: &str) -> crate::TimersetResult<()> {
+        unsupported()
+    }
+}
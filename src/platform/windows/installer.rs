@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use winapi::um::taskschd::TASK_CREATE_OR_UPDATE;
+
+use crate::platform::TaskInstaller;
+use crate::task_scheduler::{
+    ExecAction, LogonTrigger, RegisterTaskDefinitionArgs, TaskActionType, TaskCompatibility,
+    TaskInstancesPolicy, TaskLogonType, TaskRunlevel, TaskTriggerType, WindowsTaskScheduler,
+};
+use crate::utils::StartArgs;
+
+/// Installs timerset in %ProgramFiles% and runs it at logon through the Windows Task Scheduler
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TaskSchedulerInstaller;
+
+impl TaskInstaller for TaskSchedulerInstaller {
+    fn install_dir(&self) -> crate::TimersetResult<PathBuf> {
+        let mut dest_path: PathBuf = std::env::var("PROGRAMFILES")?.into();
+        dest_path.push("TimerSet");
+        Ok(dest_path)
+    }
+
+    fn copy_executable(&mut self, dest: &Path) -> crate::TimersetResult<()> {
+        let current_exe_path = std::env::current_exe()?;
+        debug!("Current exe path: {:?}", current_exe_path);
+
+        if let Some(parent) = dest.parent() {
+            debug!("Creating app folder");
+            std::fs::create_dir_all(parent)?;
+        }
+
+        debug!(
+            "Moving timerset.exe from {:?} to {:?}",
+            current_exe_path, dest
+        );
+        std::fs::copy(current_exe_path, dest)?;
+        Ok(())
+    }
+
+    fn remove_install_dir(&mut self, dir: &Path) -> crate::TimersetResult<()> {
+        let _ = std::fs::remove_dir_all(dir);
+        Ok(())
+    }
+
+    fn register_startup_task(
+        &mut self,
+        name: &str,
+        start_args: &StartArgs,
+    ) -> crate::TimersetResult<()> {
+        let scheduler = WindowsTaskScheduler::new()?;
+        scheduler.connect()?;
+
+        let folder = scheduler.folder("\\")?;
+        folder.delete_task(name)?;
+        let task = scheduler.new_task()?;
+
+        let task_principal = task.principal()?;
+        task_principal.set_group_id("NT AUTHORITY\\SYSTEM")?;
+        task_principal.set_logon_type(TaskLogonType::Group)?;
+        task_principal.set_runlevel(TaskRunlevel::Highest)?;
+
+        let task_reginfo = task.registration_info()?;
+        task_reginfo.set_author("Mathieu \"OtaK_\" Amiot")?;
+        task_reginfo
+            .set_description("Start TimerSet at logon of any user with admin permissions")?;
+
+        let task_settings = task.settings()?;
+        task_settings.set_start_when_available(true)?;
+        task_settings.set_enabled(true)?;
+        task_settings.set_hidden(false)?;
+        task_settings.set_multiple_instances(TaskInstancesPolicy::StopExisting)?;
+        task_settings.set_execution_time_limit("PT0S")?;
+        task_settings.set_compatibility(TaskCompatibility::V24)?;
+
+        let triggers = task.triggers()?;
+        let raw_trigger = triggers.create(TaskTriggerType::Logon)?;
+        use crate::task_scheduler::SubTrigger as _;
+        let logon_trigger = LogonTrigger::new(raw_trigger)?;
+        logon_trigger.trigger().set_execution_time_limit("PT0S")?;
+        logon_trigger.set_delay("PT10S")?;
+
+        let action_collection = task.actions()?;
+        let raw_task = action_collection.create(TaskActionType::Exec)?;
+        use crate::task_scheduler::SubAction as _;
+        let exec_action = ExecAction::new(raw_task)?;
+        exec_action.set_path(&start_args.target)?;
+        exec_action.set_arguments(StartArgs::args_to_string(&start_args.args))?;
+        if let Some(start_location) = &start_args.start_location {
+            exec_action.set_working_directory(start_location)?;
+        }
+
+        let _ = folder.register_task_definition(RegisterTaskDefinitionArgs {
+            task_name: name,
+            task_definition: task,
+            flags: TASK_CREATE_OR_UPDATE as _,
+            user_id: None,
+            password: None,
+            logon_type: TaskLogonType::InteractiveToken,
+            sddl: None,
+        })?;
+
+        Ok(())
+    }
+
+    fn delete_startup_task(&mut self, name: &str) -> crate::TimersetResult<()> {
+        let scheduler = WindowsTaskScheduler::new()?;
+        scheduler.connect()?;
+
+        let folder = scheduler.folder("\\")?;
+        folder.delete_task(name)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use ntapi::ntexapi::{
+    NtQuerySystemInformation, NtSetSystemInformation, SystemMemoryListInformation,
+    SYSTEM_MEMORY_LIST_INFORMATION,
+};
+
+use winapi::um::{
+    handleapi::CloseHandle,
+    processthreadsapi::{GetCurrentProcess, OpenProcessToken},
+    securitybaseapi::AdjustTokenPrivileges,
+    sysinfoapi::{GetSystemInfo, GlobalMemoryStatusEx, MEMORYSTATUSEX, SYSTEM_INFO},
+    winbase::LookupPrivilegeValueA,
+    winnt::{SE_PRIVILEGE_ENABLED, TOKEN_ADJUST_PRIVILEGES, TOKEN_PRIVILEGES, TOKEN_QUERY},
+};
+
+use log::debug;
+
+use crate::platform::{MemoryBackend, MemoryListInfo, PurgeCommand};
+
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+struct SystemMemoryListInformationWrapper(SYSTEM_MEMORY_LIST_INFORMATION);
+
+impl std::fmt::Debug for SystemMemoryListInformationWrapper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SystemMemoryListInformation")
+            .field("ZeroPageCount", &self.0.ZeroPageCount)
+            .field("FreePageCount", &self.0.FreePageCount)
+            .field("ModifiedPageCount", &self.0.ModifiedPageCount)
+            .field("BadPageCount", &self.0.BadPageCount)
+            .field("PageCountByPriority", &self.0.PageCountByPriority)
+            .field(
+                "RepurposedPagesByPriority",
+                &self.0.RepurposedPagesByPriority,
+            )
+            .field(
+                "ModifiedPageCountPageFile",
+                &self.0.ModifiedPageCountPageFile,
+            )
+            .finish()
+    }
+}
+
+impl From<SystemMemoryListInformationWrapper> for MemoryListInfo {
+    fn from(info: SystemMemoryListInformationWrapper) -> Self {
+        let info = info.0;
+        Self {
+            zero_page_count: info.ZeroPageCount,
+            free_page_count: info.FreePageCount,
+            modified_page_count: info.ModifiedPageCount,
+            bad_page_count: info.BadPageCount,
+            page_count_by_priority: info.PageCountByPriority,
+            repurposed_pages_by_priority: info.RepurposedPagesByPriority,
+            modified_page_count_page_file: info.ModifiedPageCountPageFile,
+        }
+    }
+}
+
+/// NT Kernel memory lists, driven through `NtQuerySystemInformation`/`NtSetSystemInformation`
+#[derive(Debug)]
+pub struct NtMemory {
+    page_size: usize,
+}
+
+impl Default for NtMemory {
+    fn default() -> Self {
+        let mut system_info = SYSTEM_INFO::default();
+        unsafe { GetSystemInfo(&mut system_info as _) };
+        debug!("System page size is {}", system_info.dwPageSize);
+
+        Self {
+            page_size: system_info.dwPageSize as usize,
+        }
+    }
+}
+
+impl MemoryBackend for NtMemory {
+    fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    fn total_physical_memory(&mut self) -> crate::TimersetResult<usize> {
+        let mut status = MEMORYSTATUSEX {
+            dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
+            ..Default::default()
+        };
+        crate::w32_ok!(BOOL GlobalMemoryStatusEx(&mut status))?;
+        Ok(status.ullTotalPhys as usize)
+    }
+
+    fn acquire_privileges(&mut self) -> crate::TimersetResult<()> {
+        debug!("Beginning to upgrade security token...");
+        let process_hwnd = unsafe { GetCurrentProcess() };
+        let mut token_hwnd = winapi::shared::ntdef::NULL;
+        crate::w32_ok!(BOOL OpenProcessToken(
+            process_hwnd,
+            TOKEN_QUERY | TOKEN_ADJUST_PRIVILEGES,
+            &mut token_hwnd,
+        ))?;
+
+        let mut luid = winapi::shared::ntdef::LUID::default();
+        let lp_name = unsafe {
+            std::ffi::CStr::from_bytes_with_nul_unchecked(b"SeProfileSingleProcessPrivilege\0")
+        };
+        crate::w32_ok!(BOOL LookupPrivilegeValueA(0 as _, lp_name.as_ptr() as _, &mut luid as _))?;
+
+        debug!(
+            "LookupPrivilegeValueA returned LUID Low = {:x} / High = {:x}",
+            luid.LowPart, luid.HighPart
+        );
+
+        let mut new_privileges = TOKEN_PRIVILEGES::default();
+        let mut old_privileges = TOKEN_PRIVILEGES::default();
+        let mut dw_buffer_length = 16u32;
+
+        new_privileges.PrivilegeCount = 1;
+        new_privileges.Privileges[0].Luid = luid;
+        new_privileges.Privileges[0].Attributes = 0;
+        crate::w32_ok!(BOOL AdjustTokenPrivileges(
+            token_hwnd,
+            0,
+            &mut new_privileges as _,
+            std::mem::size_of_val(&new_privileges) as _,
+            &mut old_privileges as _,
+            &mut dw_buffer_length as _,
+        ))?;
+
+        debug!("Assigned new privileges successfully");
+
+        old_privileges.PrivilegeCount = 1;
+        old_privileges.Privileges[0].Luid = luid;
+        old_privileges.Privileges[0].Attributes = SE_PRIVILEGE_ENABLED;
+        crate::w32_ok!(BOOL AdjustTokenPrivileges(
+            token_hwnd,
+            0,
+            &mut old_privileges,
+            dw_buffer_length,
+            winapi::shared::ntdef::NULL as _,
+            0 as _,
+        ))?;
+
+        debug!("Assigned old privileges successfully");
+
+        crate::w32_ok!(BOOL CloseHandle(token_hwnd))?;
+        crate::w32_ok!(BOOL CloseHandle(process_hwnd))?;
+        debug!("Closed process & token handles successfully");
+
+        Ok(())
+    }
+
+    fn query_memory_lists(&mut self) -> crate::TimersetResult<MemoryListInfo> {
+        let mut system_information: SYSTEM_MEMORY_LIST_INFORMATION = unsafe { std::mem::zeroed() };
+        let mut ret_len = 0u32;
+
+        debug!("Calling NtQuerySystemInformation...");
+        // Calling NtQuerySystemInformation with the undocumented SystemMemoryListInformation parameter allows
+        // to retrieve the stats of cached/freed/zeroed pages.
+        crate::w32_ok!(DEBUG
+            NtQuerySystemInformation(
+                SystemMemoryListInformation,
+                &mut system_information as *mut SYSTEM_MEMORY_LIST_INFORMATION as _,
+                std::mem::size_of::<SYSTEM_MEMORY_LIST_INFORMATION>() as _,
+                &mut ret_len as _,
+            ),
+            |result| debug!(
+                "NtQuerySystemInformation(\n{}, \n{:?}, \n{}, \n{}\n) -> {}",
+                SystemMemoryListInformation,
+                SystemMemoryListInformationWrapper(system_information),
+                std::mem::size_of::<SYSTEM_MEMORY_LIST_INFORMATION>(),
+                ret_len,
+                result
+            )
+        )?;
+
+        Ok(SystemMemoryListInformationWrapper(system_information).into())
+    }
+
+    fn purge(&mut self, command: PurgeCommand) -> crate::TimersetResult<()> {
+        let mut command = match command {
+            PurgeCommand::EmptyWorkingSets => ntapi::ntexapi::MemoryEmptyWorkingSets,
+            PurgeCommand::FlushModifiedList => ntapi::ntexapi::MemoryFlushModifiedList,
+            PurgeCommand::PurgeStandbyList => ntapi::ntexapi::MemoryPurgeStandbyList,
+            PurgeCommand::PurgeLowPriorityStandbyList => {
+                ntapi::ntexapi::MemoryPurgeLowPriorityStandbyList
+            }
+        };
+        let cmd_len = std::mem::size_of_val(&command) as u32;
+        let cmd_ptr: *mut u32 = &mut command as _;
+
+        // Calling NtSetSystemInformation with one of the undocumented SYSTEM_MEMORY_LIST_COMMANDs
+        // such as MemoryPurgeStandbyList triggers purging the StandbyList, allowing to reclaim cached physical memory as free.
+        // This command is usually blocking for a few seconds since the kernel call blocks until
+        // all of the standby list is freed
+        crate::w32_ok!(DEBUG
+            NtSetSystemInformation(SystemMemoryListInformation, cmd_ptr as _, cmd_len),
+            |result| {
+                debug!(
+                    "NtSetSystemInformation({}, {:?}, {}) -> {}",
+                    SystemMemoryListInformation, cmd_ptr, cmd_len, result
+                );
+
+                if result == winapi::shared::ntstatus::STATUS_PRIVILEGE_NOT_HELD {
+                    debug!("Lacking admin token to do such an action");
+                }
+            }
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+mod installer;
+mod memory;
+mod notification;
+mod power;
+mod process;
+mod registry;
+mod shutdown;
+mod timer;
+mod version;
+
+pub use self::installer::*;
+pub use self::memory::*;
+pub use self::notification::*;
+pub use self::power::*;
+pub use self::process::*;
+pub use self::registry::*;
+pub use self::shutdown::*;
+pub use self::timer::*;
+pub use self::version::*;
+
+pub use crate::utils::win_elevated::is_app_elevated as is_elevated;
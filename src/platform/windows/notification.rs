@@ -0,0 +1,84 @@
+use log::debug;
+use winapi::shared::winerror::WAIT_TIMEOUT;
+use winapi::um::{
+    handleapi::CloseHandle,
+    synchapi::WaitForSingleObject,
+    winbase::{WAIT_ABANDONED, WAIT_FAILED, WAIT_OBJECT_0},
+};
+
+use super::{registry_journal_path, WinRegistry};
+use crate::platform::{MemoryNotification, RegistryJournal, MEMORY_MANAGEMENT_KEY};
+
+/// Low memory notifications through `CreateMemoryResourceNotification`,
+/// which fires once free memory goes below the `LowMemoryThreshold` registry value.
+/// The previous `LowMemoryThreshold` is journaled on disk and restored on disarm, or on the next run after a crash.
+#[derive(Debug)]
+pub struct CmrnNotification {
+    journal: RegistryJournal<WinRegistry>,
+    memory_hwnd: winapi::shared::ntdef::HANDLE,
+}
+
+impl Default for CmrnNotification {
+    fn default() -> Self {
+        Self {
+            journal: RegistryJournal::new(WinRegistry, registry_journal_path()),
+            memory_hwnd: winapi::shared::ntdef::NULL,
+        }
+    }
+}
+
+impl Drop for CmrnNotification {
+    fn drop(&mut self) {
+        let _ = self.disarm();
+    }
+}
+
+impl MemoryNotification for CmrnNotification {
+    fn arm(&mut self, freemem_threshold: usize) -> crate::TimersetResult<()> {
+        self.journal.set_u32(
+            MEMORY_MANAGEMENT_KEY,
+            "LowMemoryThreshold",
+            u32::try_from(freemem_threshold).unwrap_or(u32::MAX),
+        )?;
+
+        // Here we create a MemoryResourceNotification handle that will be triggered when the set LowMemoryThreshold
+        // will be reached. `wait` takes care of that with a blocking call on `WaitForSingleObject`
+        self.memory_hwnd = unsafe {
+            winapi::um::memoryapi::CreateMemoryResourceNotification(
+                winapi::um::memoryapi::LowMemoryResourceNotification,
+            )
+        };
+
+        if self.memory_hwnd.is_null() {
+            let error = crate::TimersetError::windows_error();
+            self.journal.restore()?;
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    fn wait(&mut self, timeout: std::time::Duration) -> crate::TimersetResult<bool> {
+        let timeout_ms = u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX - 1);
+        let result = unsafe { WaitForSingleObject(self.memory_hwnd, timeout_ms) };
+        match result {
+            WAIT_FAILED => Err(crate::TimersetError::windows_error()),
+            WAIT_OBJECT_0 | WAIT_ABANDONED => Ok(true),
+            WAIT_TIMEOUT => Ok(false),
+            _ => unreachable!(),
+        }
+    }
+
+    fn disarm(&mut self) -> crate::TimersetResult<()> {
+        if self.memory_hwnd.is_null() {
+            return Ok(());
+        }
+
+        debug!("Cleaning up memory handle");
+        unsafe { CloseHandle(self.memory_hwnd) };
+        self.memory_hwnd = winapi::shared::ntdef::NULL;
+
+        debug!("Restoring LowMemoryThreshold registry value...");
+        self.journal.restore().map(|_| ())
+    }
+}
@@ -0,0 +1,25 @@
+use winapi::um::winbase::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+use crate::platform::{PowerSource, PowerState};
+
+/// `ACLineStatus` value when running on battery; it's 1 on AC power and 255 when unknown
+const AC_LINE_OFFLINE: u8 = 0;
+
+/// Power source, as reported by `GetSystemPowerStatus`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemPowerStatus;
+
+impl PowerSource for SystemPowerStatus {
+    fn power_state(&mut self) -> crate::TimersetResult<PowerState> {
+        let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+        if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+            return Err(crate::TimersetError::windows_error());
+        }
+
+        Ok(if status.ACLineStatus == AC_LINE_OFFLINE {
+            PowerState::Battery
+        } else {
+            PowerState::Ac
+        })
+    }
+}
@@ -0,0 +1,95 @@
+use winapi::shared::minwindef::{FALSE, FILETIME};
+use winapi::um::{
+    handleapi::{CloseHandle, INVALID_HANDLE_VALUE},
+    minwinbase::STILL_ACTIVE,
+    processthreadsapi::{GetExitCodeProcess, GetProcessTimes, OpenProcess},
+    tlhelp32::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    },
+    winnt::PROCESS_QUERY_LIMITED_INFORMATION,
+};
+
+use crate::platform::ProcessSource;
+
+/// Running processes, as listed by a Toolhelp snapshot
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ToolhelpProcesses;
+
+/// Process IDs and executable file names of the running processes
+fn process_entries() -> crate::TimersetResult<Vec<(u32, String)>> {
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+    if snapshot == INVALID_HANDLE_VALUE {
+        return Err(crate::TimersetError::windows_error());
+    }
+
+    let mut entry: PROCESSENTRY32W = unsafe { std::mem::zeroed() };
+    entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+
+    let mut entries = vec![];
+    let mut found = unsafe { Process32FirstW(snapshot, &mut entry) } != 0;
+    while found {
+        // szExeFile is the bare, nul-terminated executable file name
+        let len = entry
+            .szExeFile
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(entry.szExeFile.len());
+        entries.push((
+            entry.th32ProcessID,
+            String::from_utf16_lossy(&entry.szExeFile[..len]),
+        ));
+        found = unsafe { Process32NextW(snapshot, &mut entry) } != 0;
+    }
+
+    unsafe { CloseHandle(snapshot) };
+    Ok(entries)
+}
+
+impl ProcessSource for ToolhelpProcesses {
+    fn running_processes(&mut self) -> crate::TimersetResult<Vec<String>> {
+        Ok(process_entries()?
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect())
+    }
+}
+
+/// Process IDs of the other running timerset instances, matched on the executable file name
+pub fn running_instances() -> crate::TimersetResult<Vec<u32>> {
+    let exe = std::env::current_exe()?;
+    let own_name = exe
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let own_pid = std::process::id();
+    Ok(process_entries()?
+        .into_iter()
+        .filter(|(pid, name)| *pid != own_pid && name.to_lowercase() == own_name)
+        .map(|(pid, _)| pid)
+        .collect())
+}
+
+/// Creation time of a running process, as a FILETIME; `None` if it isn't running.
+/// Tells a process apart from a later one that got the same ID.
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid);
+        if process.is_null() {
+            return None;
+        }
+
+        let mut exit_code = 0;
+        let mut creation: FILETIME = std::mem::zeroed();
+        let mut exit: FILETIME = std::mem::zeroed();
+        let mut kernel: FILETIME = std::mem::zeroed();
+        let mut user: FILETIME = std::mem::zeroed();
+        let running = GetExitCodeProcess(process, &mut exit_code) != 0
+            && exit_code == STILL_ACTIVE
+            && GetProcessTimes(process, &mut creation, &mut exit, &mut kernel, &mut user) != 0;
+        CloseHandle(process);
+
+        running
+            .then(|| (u64::from(creation.dwHighDateTime) << 32) | u64::from(creation.dwLowDateTime))
+    }
+}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use crate::platform::RegistryBackend;
+
+/// Location of the registry journal, in %ProgramData%\TimerSet
+pub fn registry_journal_path() -> PathBuf {
+    let mut path: PathBuf = std::env::var("PROGRAMDATA")
+        .unwrap_or_else(|_| "C:\\ProgramData".into())
+        .into();
+    path.push("TimerSet");
+    path.push("registry.journal");
+    path
+}
+
+/// Registry access through `winreg`, rooted at HKEY_LOCAL_MACHINE
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WinRegistry;
+
+impl WinRegistry {
+    fn open(path: &str, flags: winreg::enums::REGSAM) -> std::io::Result<winreg::RegKey> {
+        winreg::RegKey::predef(winreg::enums::HKEY_LOCAL_MACHINE)
+            .open_subkey_with_flags(path, flags)
+    }
+}
+
+impl RegistryBackend for WinRegistry {
+    fn get_u32(&self, path: &str, name: &str) -> crate::TimersetResult<Option<u32>> {
+        let key = Self::open(path, winreg::enums::KEY_READ)?;
+        match key.get_value::<u32, _>(name) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_u32(&mut self, path: &str, name: &str, value: u32) -> crate::TimersetResult<()> {
+        let key = Self::open(path, winreg::enums::KEY_WRITE)?;
+        key.set_value(name, &value)?;
+        Ok(())
+    }
+
+    fn delete_value(&mut self, path: &str, name: &str) -> crate::TimersetResult<()> {
+        let key = Self::open(path, winreg::enums::KEY_WRITE)?;
+        key.delete_value(name)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use log::{debug, warn};
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, LPARAM, LRESULT, TRUE, UINT, WPARAM};
+use winapi::shared::windef::HWND;
+use winapi::um::consoleapi::SetConsoleCtrlHandler;
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::libloaderapi::GetModuleHandleW;
+use winapi::um::processthreadsapi::{OpenProcess, TerminateProcess};
+use winapi::um::wincon::{
+    CTRL_BREAK_EVENT, CTRL_CLOSE_EVENT, CTRL_C_EVENT, CTRL_LOGOFF_EVENT, CTRL_SHUTDOWN_EVENT,
+};
+use winapi::um::winnt::PROCESS_TERMINATE;
+use winapi::um::winuser::{
+    CreateWindowExW, DefWindowProcW, DispatchMessageW, EnumWindows, GetClassNameW, GetMessageW,
+    GetWindowThreadProcessId, PostMessageW, RegisterClassW, TranslateMessage, ENDSESSION_LOGOFF,
+    MSG, WM_CLOSE, WM_ENDSESSION, WM_QUERYENDSESSION, WNDCLASSW,
+};
+
+use crate::shutdown::{Shutdown, ShutdownReason};
+
+/// Windows terminates the process 5 seconds after a close, logoff or shutdown event anyway
+const CLEANUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Class of the hidden window receiving session end events, and shutdown requests from `stop_instance`
+const SESSION_WINDOW_CLASS: &str = "TimerSetSessionWindow";
+
+static SHUTDOWN: OnceLock<Shutdown> = OnceLock::new();
+
+/// Requests a shutdown and holds off process termination until cleaning up is done
+fn request_and_wait(reason: ShutdownReason) {
+    if let Some(shutdown) = SHUTDOWN.get() {
+        shutdown.request(reason);
+        if !shutdown.wait_completed(CLEANUP_TIMEOUT) {
+            warn!("Cleaning up took too long, letting Windows terminate the process");
+        }
+    }
+}
+
+unsafe extern "system" fn console_handler(ctrl_type: DWORD) -> BOOL {
+    debug!("Received console control event {}", ctrl_type);
+    match ctrl_type {
+        // The process keeps running after these, main exits by itself once cleaned up
+        CTRL_C_EVENT | CTRL_BREAK_EVENT => {
+            if let Some(shutdown) = SHUTDOWN.get() {
+                shutdown.request(ShutdownReason::Interrupt);
+            }
+        }
+        // The process gets terminated as soon as these return
+        CTRL_CLOSE_EVENT => request_and_wait(ShutdownReason::Terminate),
+        CTRL_LOGOFF_EVENT => request_and_wait(ShutdownReason::Logoff),
+        CTRL_SHUTDOWN_EVENT => request_and_wait(ShutdownReason::SystemShutdown),
+        _ => return FALSE,
+    }
+    TRUE
+}
+
+unsafe extern "system" fn session_window_proc(
+    hwnd: HWND,
+    msg: UINT,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_QUERYENDSESSION => TRUE as LRESULT,
+        WM_ENDSESSION if wparam != 0 => {
+            if lparam as UINT & ENDSESSION_LOGOFF != 0 {
+                request_and_wait(ShutdownReason::Logoff);
+            } else {
+                request_and_wait(ShutdownReason::SystemShutdown);
+            }
+            0
+        }
+        // Sent by `stop_instance`; the window stays around until the process exits
+        WM_CLOSE => {
+            if let Some(shutdown) = SHUTDOWN.get() {
+                shutdown.request(ShutdownReason::Terminate);
+            }
+            0
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+/// Processes without a console (i.e. release builds) never get console control events, and
+/// console processes don't get logoff/shutdown ones either once user32 is loaded.
+/// Session end is signaled to top-level windows instead, so this creates a hidden one and pumps its messages.
+fn run_session_window() -> crate::TimersetResult<()> {
+    let class_name: Vec<u16> = SESSION_WINDOW_CLASS
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    unsafe {
+        let instance = GetModuleHandleW(std::ptr::null());
+        let mut class: WNDCLASSW = std::mem::zeroed();
+        class.lpfnWndProc = Some(session_window_proc);
+        class.hInstance = instance;
+        class.lpszClassName = class_name.as_ptr();
+        if RegisterClassW(&class) == 0 {
+            return Err(crate::TimersetError::windows_error());
+        }
+
+        let hwnd = CreateWindowExW(
+            0,
+            class_name.as_ptr(),
+            class_name.as_ptr(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            instance,
+            std::ptr::null_mut(),
+        );
+        if hwnd.is_null() {
+            return Err(crate::TimersetError::windows_error());
+        }
+
+        let mut msg: MSG = std::mem::zeroed();
+        while GetMessageW(&mut msg, std::ptr::null_mut(), 0, 0) > 0 {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    }
+    Ok(())
+}
+
+/// Requests a shutdown on Ctrl+C, console close, logoff and system shutdown
+pub fn install_shutdown_handler(shutdown: Shutdown) -> crate::TimersetResult<()> {
+    if SHUTDOWN.set(shutdown).is_err() {
+        return Ok(());
+    }
+
+    if unsafe { SetConsoleCtrlHandler(Some(console_handler), TRUE) } == 0 {
+        return Err(crate::TimersetError::windows_error());
+    }
+
+    std::thread::spawn(|| {
+        if let Err(e) = run_session_window() {
+            warn!("Could not listen to session end events: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+/// `EnumWindows` callback posting `WM_CLOSE` to the session window of the process whose ID is in `lparam`
+unsafe extern "system" fn close_session_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let target = &mut *(lparam as *mut (DWORD, bool));
+    let mut pid = 0;
+    GetWindowThreadProcessId(hwnd, &mut pid);
+    if pid != target.0 {
+        return TRUE;
+    }
+
+    let mut class = [0u16; 64];
+    let len = GetClassNameW(hwnd, class.as_mut_ptr(), class.len() as i32);
+    if len > 0 && String::from_utf16_lossy(&class[..len as usize]) == SESSION_WINDOW_CLASS {
+        target.1 = PostMessageW(hwnd, WM_CLOSE, 0, 0) != 0;
+        return FALSE;
+    }
+    TRUE
+}
+
+/// Asks another timerset instance to shut down through its hidden session window.
+/// Windows of other sessions can't be reached, so instances running there get terminated instead:
+/// the kernel drops their timer request on exit, and the registry journal gets recovered on the next run.
+pub fn stop_instance(pid: u32) -> crate::TimersetResult<()> {
+    let mut target: (DWORD, bool) = (pid, false);
+    unsafe { EnumWindows(Some(close_session_window), &mut target as *mut _ as LPARAM) };
+    if target.1 {
+        return Ok(());
+    }
+
+    warn!(
+        "timerset instance {} can't be asked to shut down, terminating it",
+        pid
+    );
+    unsafe {
+        let process = OpenProcess(PROCESS_TERMINATE, FALSE, pid);
+        if process.is_null() {
+            return Err(crate::TimersetError::windows_error());
+        }
+        let terminated = TerminateProcess(process, 1);
+        CloseHandle(process);
+        if terminated == 0 {
+            return Err(crate::TimersetError::windows_error());
+        }
+    }
+    Ok(())
+}
@@ -1,35 +1,30 @@
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
-pub struct TimerResolutionInfo {
-    pub cur: u32,
-    pub min: u32,
-    pub max: u32,
-}
+use crate::platform::{TimerBackend, TimerRange};
+use crate::timer::TimerResolution;
 
-impl std::fmt::Display for TimerResolutionInfo {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
-            f,
-            "System Timer Values: min [{}μs] / max [{}μs] / cur [{}μs]",
-            self.min, self.max, self.cur
-        )
-    }
-}
+/// NT Kernel timer, driven through `NtQueryTimerResolution`/`NtSetTimerResolution`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NtTimer;
 
-impl TimerResolutionInfo {
-    pub fn fetch() -> crate::TimersetResult<Self> {
+impl TimerBackend for NtTimer {
+    fn query_resolution(&self) -> crate::TimersetResult<TimerRange> {
         let mut min = 0u32;
         let mut max = 0u32;
         let mut cur = 0u32;
         // NtQueryTimerResolution is an old, undocumented internal NT Kernel API that allows to retrieve
         // the kernel's timer resolutions. It will give out minimum and maximum resolutions, and the current one.
+        // All of them are expressed in 100-nanosecond units.
         crate::w32_ok!(ntapi::ntexapi::NtQueryTimerResolution(
             &mut min, &mut max, &mut cur
         ))?;
-        Ok(Self { min, max, cur })
+        Ok(TimerRange {
+            min: TimerResolution::from_hns(min),
+            max: TimerResolution::from_hns(max),
+            cur: TimerResolution::from_hns(cur),
+        })
     }
 
-    pub fn apply_timer(&mut self, value: u32) -> crate::TimersetResult<()> {
-        let value = self.clamp_timer_value(value);
+    fn set_resolution(&mut self, value: TimerResolution) -> crate::TimersetResult<TimerResolution> {
+        let mut cur = 0u32;
         // NtSetTimerResolution is an old, undocumented internal NT Kernel API that is very often used by media applications
         // to raise the kernel's timer and allow lower latencies and higher (= closer to real-time) throughput
         // This call sets the desired timer resolution and keeps it effective as long as the calling process is running.
@@ -39,20 +34,24 @@ impl TimerResolutionInfo {
         // is essentially a lie, for the simple fact that many legacy moving parts of the kernel are still relying on the NT Timer.
         // WDDM is a good example of this for instance, as using a low timer will reduce DPC/ISR latencies for most -if not all- drivers.
         crate::w32_ok!(ntapi::ntexapi::NtSetTimerResolution(
-            value,
+            value.as_hns(),
             1,
-            &mut self.cur
+            &mut cur
         ))?;
-        Ok(())
+        Ok(TimerResolution::from_hns(cur))
     }
 
-    pub fn clamp_timer_value(&self, value: u32) -> u32 {
-        if value > self.min {
-            self.min
-        } else if value < self.max {
-            self.max
-        } else {
-            value
-        }
+    fn release_resolution(
+        &mut self,
+        value: TimerResolution,
+    ) -> crate::TimersetResult<TimerResolution> {
+        let mut cur = 0u32;
+        // Same call with `SetResolution` to FALSE: the kernel goes back to the finest resolution still requested by other processes
+        crate::w32_ok!(ntapi::ntexapi::NtSetTimerResolution(
+            value.as_hns(),
+            0,
+            &mut cur
+        ))?;
+        Ok(TimerResolution::from_hns(cur))
     }
 }
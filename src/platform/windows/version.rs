@@ -0,0 +1,10 @@
+/// Build number of the running Windows version, e.g. 22631 for Windows 11 23H2
+pub fn os_build() -> Option<u32> {
+    let mut info: winapi::um::winnt::RTL_OSVERSIONINFOW = unsafe { std::mem::zeroed() };
+    info.dwOSVersionInfoSize = std::mem::size_of_val(&info) as u32;
+    // Unlike GetVersionEx, RtlGetVersion reports the real version whatever the application manifest says
+    if unsafe { ntapi::ntrtl::RtlGetVersion(&mut info) } != 0 {
+        return None;
+    }
+    Some(info.dwBuildNumber)
+}
@@ -0,0 +1,74 @@
+//! Tracking of the power source the system runs on.
+
+use log::info;
+
+use crate::platform::{PowerSource, PowerState};
+
+/// Tracks power source transitions (AC power/battery) across successive reads
+pub struct PowerWatcher {
+    source: Box<dyn PowerSource + Send>,
+    state: Option<PowerState>,
+}
+
+impl std::fmt::Debug for PowerWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PowerWatcher")
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PowerWatcher {
+    pub fn new(source: impl PowerSource + Send + 'static) -> Self {
+        Self {
+            source: Box::new(source),
+            state: None,
+        }
+    }
+
+    /// Power state as of the last poll; AC power until the first one
+    pub fn state(&self) -> PowerState {
+        self.state.unwrap_or_default()
+    }
+
+    /// Reads the power source, and returns the new state if it changed since the last poll.
+    /// The first poll always reports the current state.
+    pub fn poll(&mut self) -> crate::TimersetResult<Option<PowerState>> {
+        let state = self.source.power_state()?;
+        if self.state == Some(state) {
+            return Ok(None);
+        }
+
+        match self.state {
+            Some(previous) => info!("Power source changed from {} to {}", previous, state),
+            None => info!("Running on {}", state),
+        }
+        self.state = Some(state);
+        Ok(Some(state))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PowerWatcher;
+    use crate::platform::sim::SimPower;
+    use crate::platform::PowerState;
+
+    #[test]
+    fn should_report_power_transitions() {
+        let power = SimPower::default();
+        let mut watcher = PowerWatcher::new(power.clone());
+        assert_eq!(watcher.state(), PowerState::Ac);
+
+        assert_eq!(watcher.poll().unwrap(), Some(PowerState::Ac));
+        assert_eq!(watcher.poll().unwrap(), None);
+
+        power.set(PowerState::Battery);
+        assert_eq!(watcher.poll().unwrap(), Some(PowerState::Battery));
+        assert_eq!(watcher.state(), PowerState::Battery);
+        assert_eq!(watcher.poll().unwrap(), None);
+
+        power.set(PowerState::Ac);
+        assert_eq!(watcher.poll().unwrap(), Some(PowerState::Ac));
+    }
+}
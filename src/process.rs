@@ -0,0 +1,229 @@
+//! Matching of running processes against executable name patterns.
+
+use std::collections::BTreeSet;
+
+use crate::platform::ProcessSource;
+
+/// Case-insensitive executable name pattern, where `*` matches any characters and `?` a single one,
+/// e.g. `*-Win64-Shipping.exe`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessPattern(String);
+
+impl ProcessPattern {
+    pub fn matches(&self, name: &str) -> bool {
+        let pattern: Vec<char> = self.0.chars().collect();
+        let name: Vec<char> = name.to_lowercase().chars().collect();
+
+        // Greedy matching, backtracking to the last `*` on mismatch
+        let (mut p, mut n) = (0, 0);
+        let mut last_star: Option<(usize, usize)> = None;
+        while n < name.len() {
+            match pattern.get(p) {
+                Some('*') => {
+                    last_star = Some((p, n));
+                    p += 1;
+                }
+                Some(&c) if c == '?' || c == name[n] => {
+                    p += 1;
+                    n += 1;
+                }
+                _ => match last_star {
+                    Some((star_p, star_n)) => {
+                        last_star = Some((star_p, star_n + 1));
+                        p = star_p + 1;
+                        n = star_n + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        pattern[p..].iter().all(|&c| c == '*')
+    }
+}
+
+impl std::fmt::Display for ProcessPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("A process list needs at least one executable name, e.g. `game.exe,*-Win64-Shipping.exe`")]
+pub struct ProcessPatternsParseError;
+
+/// Executable name patterns, matching if any of them does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessPatterns(Vec<ProcessPattern>);
+
+impl ProcessPatterns {
+    pub fn matches(&self, name: &str) -> bool {
+        self.0.iter().any(|pattern| pattern.matches(name))
+    }
+}
+
+impl std::fmt::Display for ProcessPatterns {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, pattern) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            pattern.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a comma-separated list of patterns, e.g. `game.exe,*-Win64-Shipping.exe`
+impl std::str::FromStr for ProcessPatterns {
+    type Err = ProcessPatternsParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let patterns: Vec<_> = s
+            .split(',')
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(|pattern| ProcessPattern(pattern.to_lowercase()))
+            .collect();
+
+        if patterns.is_empty() {
+            return Err(ProcessPatternsParseError);
+        }
+
+        Ok(Self(patterns))
+    }
+}
+
+/// Change in the set of running watched processes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessEvent {
+    Started(String),
+    Exited(String),
+}
+
+/// Tracks which processes matching the patterns are running, across successive process listings
+pub struct ProcessWatcher {
+    patterns: ProcessPatterns,
+    running: BTreeSet<String>,
+    source: Box<dyn ProcessSource + Send>,
+}
+
+impl std::fmt::Debug for ProcessWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProcessWatcher")
+            .field("patterns", &self.patterns)
+            .field("running", &self.running)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProcessWatcher {
+    pub fn new(patterns: ProcessPatterns, source: impl ProcessSource + Send + 'static) -> Self {
+        Self {
+            patterns,
+            running: BTreeSet::new(),
+            source: Box::new(source),
+        }
+    }
+
+    /// Whether any watched process is running, as of the last poll
+    pub fn active(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    /// Lists the running processes and returns the watched ones that started or exited since the last poll.
+    /// Watched processes already running on the first poll are reported as started.
+    pub fn poll(&mut self) -> crate::TimersetResult<Vec<ProcessEvent>> {
+        let names = self.source.running_processes()?;
+        Ok(self.update(&names))
+    }
+
+    fn update(&mut self, names: &[String]) -> Vec<ProcessEvent> {
+        // Several instances of a process count once
+        let running: BTreeSet<String> = names
+            .iter()
+            .filter(|name| self.patterns.matches(name))
+            .cloned()
+            .collect();
+
+        let events = running
+            .difference(&self.running)
+            .cloned()
+            .map(ProcessEvent::Started)
+            .chain(
+                self.running
+                    .difference(&running)
+                    .cloned()
+                    .map(ProcessEvent::Exited),
+            )
+            .collect();
+        self.running = running;
+        events
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ProcessEvent, ProcessPatterns, ProcessPatternsParseError, ProcessWatcher};
+    use crate::platform::sim::SimProcesses;
+
+    #[test]
+    fn should_match_globs_case_insensitively() {
+        let patterns: ProcessPatterns = "Game.exe, *-Win64-Shipping.exe,r5apex?.exe"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            patterns.to_string(),
+            "game.exe,*-win64-shipping.exe,r5apex?.exe"
+        );
+
+        assert!(patterns.matches("game.exe"));
+        assert!(patterns.matches("GAME.EXE"));
+        assert!(!patterns.matches("game.exe.bak"));
+        assert!(patterns.matches("Shooter-Win64-Shipping.exe"));
+        assert!(patterns.matches("-Win64-Shipping.exe"));
+        assert!(!patterns.matches("Shooter-Win64-Shipping.dll"));
+        assert!(patterns.matches("r5apex_.exe"));
+        assert!(!patterns.matches("r5apex.exe"));
+
+        assert_eq!(
+            " , ".parse::<ProcessPatterns>(),
+            Err(ProcessPatternsParseError)
+        );
+    }
+
+    #[test]
+    fn should_report_watched_process_transitions() {
+        let processes = SimProcesses::default();
+        let mut watcher = ProcessWatcher::new("*game*.exe".parse().unwrap(), processes.clone());
+
+        processes.set_running(&["explorer.exe", "steam.exe"]);
+        assert_eq!(watcher.poll().unwrap(), []);
+        assert!(!watcher.active());
+
+        processes.set_running(&["explorer.exe", "MyGame.exe", "MyGame.exe"]);
+        assert_eq!(
+            watcher.poll().unwrap(),
+            [ProcessEvent::Started("MyGame.exe".into())]
+        );
+        assert!(watcher.active());
+
+        // Still running: nothing new
+        assert_eq!(watcher.poll().unwrap(), []);
+
+        processes.set_running(&["explorer.exe", "OtherGame.exe"]);
+        assert_eq!(
+            watcher.poll().unwrap(),
+            [
+                ProcessEvent::Started("OtherGame.exe".into()),
+                ProcessEvent::Exited("MyGame.exe".into())
+            ]
+        );
+
+        processes.set_running(&["explorer.exe"]);
+        assert_eq!(
+            watcher.poll().unwrap(),
+            [ProcessEvent::Exited("OtherGame.exe".into())]
+        );
+        assert!(!watcher.active());
+    }
+}
@@ -0,0 +1,392 @@
+//! Structured reports of the informational commands, printed as JSON with `--output json`.
+//!
+//! Field names are what scripts rely on: only ever add fields, and keep `should_keep_report_fields_stable` in sync.
+//! Timer values are in 100ns units (`_hns`), memory sizes in bytes and sleep durations in nanoseconds.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::platform::{MemoryBackend, TimerBackend};
+use crate::standby::PurgeRecord;
+use crate::timer::bench::BenchResult;
+use crate::timer::{GlobalTimerRequests, TimerResolution, TimerResolutionInfo};
+
+/// How informational commands report their results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Log lines, as everything else
+    #[default]
+    Text,
+    /// A single JSON document on stdout, logs going to stderr
+    Json,
+}
+
+impl OutputFormat {
+    /// Name used on the command line
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Json => "json",
+        }
+    }
+
+    /// Prints `report` on stdout in JSON mode, otherwise runs `log_text`
+    pub fn emit<R: Serialize>(
+        self,
+        report: &R,
+        log_text: impl FnOnce(&R),
+    ) -> crate::TimersetResult<()> {
+        match self {
+            Self::Text => log_text(report),
+            Self::Json => {
+                use std::io::Write as _;
+                let mut stdout = std::io::stdout().lock();
+                serde_json::to_writer_pretty(&mut stdout, report).map_err(|e| eyre::eyre!(e))?;
+                writeln!(stdout)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("Unknown output format: {0:?}, expected text or json")]
+pub struct OutputFormatParseError(String);
+
+impl std::str::FromStr for OutputFormat {
+    type Err = OutputFormatParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Text, Self::Json]
+            .into_iter()
+            .find(|format| format.name() == s.trim())
+            .ok_or_else(|| OutputFormatParseError(s.into()))
+    }
+}
+
+/// Timer resolution range of the system, along with the value timerset would request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TimerReport {
+    pub min_hns: u32,
+    pub max_hns: u32,
+    pub cur_hns: u32,
+    pub chosen_hns: u32,
+    /// Whether timer values apply to the whole system. Otherwise (Linux timer slack) they only apply
+    /// to timerset's own threads, and `cur_hns` is the value of the calling thread.
+    pub system_wide: bool,
+}
+
+impl TimerReport {
+    pub fn new<T: TimerBackend>(info: &TimerResolutionInfo<T>, chosen: TimerResolution) -> Self {
+        Self {
+            min_hns: info.min.as_hns(),
+            max_hns: info.max.as_hns(),
+            cur_hns: info.cur.as_hns(),
+            chosen_hns: chosen.as_hns(),
+            system_wide: info.system_wide(),
+        }
+    }
+}
+
+/// Memory list statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MemoryReport {
+    pub page_size: usize,
+    pub total_bytes: usize,
+    pub free_bytes: usize,
+    pub modified_bytes: usize,
+    pub standby_bytes: usize,
+    /// Standby List size of each priority, from 0 to 7
+    pub standby_by_priority_bytes: [usize; 8],
+}
+
+impl MemoryReport {
+    pub fn query<M: MemoryBackend>(memory: &mut M) -> crate::TimersetResult<Self> {
+        memory.acquire_privileges()?;
+        let page_size = memory.page_size();
+        let total_bytes = memory.total_physical_memory()?;
+        let lists = memory.query_memory_lists()?;
+        Ok(Self {
+            page_size,
+            total_bytes,
+            free_bytes: lists.free_pages() * page_size,
+            modified_bytes: lists.modified_page_count * page_size,
+            standby_bytes: lists.standby_pages() * page_size,
+            standby_by_priority_bytes: lists.page_count_by_priority.map(|pages| pages * page_size),
+        })
+    }
+}
+
+/// Installation status of timerset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstallReport {
+    pub installed: bool,
+    /// Where timerset gets installed, if the platform supports installing it
+    pub install_dir: Option<PathBuf>,
+    pub global_timer_requests: GlobalTimerRequests,
+}
+
+/// Report of `--values`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValuesReport {
+    pub timer: TimerReport,
+    /// Missing when the memory lists couldn't be queried, e.g. without administrator permissions
+    pub memory: Option<MemoryReport>,
+    pub install: InstallReport,
+}
+
+/// Report of the `probe` command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProbeReport {
+    /// Distinct resolutions granted by the system, finest first
+    pub achievable_hns: Vec<u32>,
+}
+
+impl ProbeReport {
+    pub fn new(achievable: &[TimerResolution]) -> Self {
+        Self {
+            achievable_hns: achievable.iter().map(|value| value.as_hns()).collect(),
+        }
+    }
+}
+
+/// Sleep precision measured for a single timer resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BenchEntry {
+    pub requested_hns: u32,
+    pub applied_hns: u32,
+    pub samples: usize,
+    pub mean_ns: u64,
+    pub p50_ns: u64,
+    pub p99_ns: u64,
+    pub max_ns: u64,
+}
+
+/// Report of the `bench` command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BenchReport {
+    pub results: Vec<BenchEntry>,
+    pub recommended_hns: Option<u32>,
+}
+
+impl BenchReport {
+    pub fn new(results: &[BenchResult], recommended: Option<&BenchResult>) -> Self {
+        let ns = |d: std::time::Duration| d.as_nanos() as u64;
+        Self {
+            results: results
+                .iter()
+                .map(|result| BenchEntry {
+                    requested_hns: result.requested.as_hns(),
+                    applied_hns: result.applied.as_hns(),
+                    samples: result.stats.samples,
+                    mean_ns: ns(result.stats.mean),
+                    p50_ns: ns(result.stats.p50),
+                    p99_ns: ns(result.stats.p99),
+                    max_ns: ns(result.stats.max),
+                })
+                .collect(),
+            recommended_hns: recommended.map(|result| result.requested.as_hns()),
+        }
+    }
+}
+
+/// Report of the `reset` command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResetReport {
+    /// Process ids of the timerset instances asked to shut down
+    pub stopped: Vec<u32>,
+    pub cur_hns: u32,
+}
+
+/// A purge that would have happened during a replay
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PurgeEntry {
+    /// RFC 3339, UTC
+    pub timestamp: String,
+    pub reason: String,
+    pub reclaimed_bytes: usize,
+    pub free_before_bytes: usize,
+    pub free_after_bytes: usize,
+    pub standby_before_bytes: usize,
+    pub standby_after_bytes: usize,
+}
+
+/// Report of the `simulate` command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SimulateReport {
+    pub samples: usize,
+    pub purges: Vec<PurgeEntry>,
+    pub total_reclaimed_bytes: usize,
+}
+
+impl SimulateReport {
+    pub fn new(samples: usize, purges: &[PurgeRecord]) -> crate::TimersetResult<Self> {
+        let purges = purges
+            .iter()
+            .map(|purge| {
+                Ok(PurgeEntry {
+                    timestamp: purge
+                        .timestamp
+                        .format(&time::format_description::well_known::Rfc3339)
+                        .map_err(|e| eyre::eyre!(e))?,
+                    reason: purge.reason.to_string(),
+                    reclaimed_bytes: purge.reclaimed(),
+                    free_before_bytes: purge.free_before,
+                    free_after_bytes: purge.free_after,
+                    standby_before_bytes: purge.standby_before,
+                    standby_after_bytes: purge.standby_after,
+                })
+            })
+            .collect::<crate::TimersetResult<Vec<_>>>()?;
+        Ok(Self {
+            samples,
+            total_reclaimed_bytes: purges.iter().map(|purge| purge.reclaimed_bytes).sum(),
+            purges,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::{
+        BenchEntry, BenchReport, InstallReport, MemoryReport, OutputFormat, ProbeReport,
+        PurgeEntry, ResetReport, SimulateReport, TimerReport, ValuesReport,
+    };
+    use crate::platform::sim::{SimMemory, SimTimer};
+    use crate::timer::{GlobalTimerRequests, TimerResolution, TimerResolutionInfo};
+
+    /// Field names of a serialized object, sorted
+    fn fields<R: serde::Serialize>(report: &R) -> Vec<String> {
+        match serde_json::to_value(report).unwrap() {
+            Value::Object(map) => map.keys().cloned().collect(),
+            value => panic!("Not an object: {}", value),
+        }
+    }
+
+    #[test]
+    fn should_parse_output_formats() {
+        assert_eq!("json".parse(), Ok(OutputFormat::Json));
+        assert_eq!("text".parse(), Ok(OutputFormat::Text));
+        assert!("yaml".parse::<OutputFormat>().is_err());
+        assert_eq!(OutputFormat::Json.to_string(), "json");
+    }
+
+    #[test]
+    fn should_report_memory_lists_in_bytes() {
+        let memory = MemoryReport::query(&mut SimMemory::new(1 << 30, 2 << 30)).unwrap();
+        assert_eq!(memory.page_size, 4096);
+        assert_eq!(memory.total_bytes, 16 << 30);
+        assert_eq!(memory.free_bytes, 1 << 30);
+        assert_eq!(memory.standby_bytes, 2 << 30);
+        assert_eq!(
+            memory.standby_by_priority_bytes.iter().sum::<usize>(),
+            2 << 30
+        );
+    }
+
+    #[test]
+    fn should_keep_report_fields_stable() {
+        let info = TimerResolutionInfo::fetch_with(SimTimer::new(156250, 5000, 156250)).unwrap();
+        let values = ValuesReport {
+            timer: TimerReport::new(&info, TimerResolution::from_millis(1)),
+            memory: Some(MemoryReport::query(&mut SimMemory::new(1 << 30, 2 << 30)).unwrap()),
+            install: InstallReport {
+                installed: false,
+                install_dir: None,
+                global_timer_requests: GlobalTimerRequests::Disabled,
+            },
+        };
+        assert_eq!(fields(&values), ["install", "memory", "timer"]);
+        assert_eq!(
+            serde_json::to_value(values.timer).unwrap(),
+            json!({
+                "min_hns": 156250,
+                "max_hns": 5000,
+                "cur_hns": 156250,
+                "chosen_hns": 10000,
+                "system_wide": true
+            })
+        );
+        assert_eq!(
+            fields(&values.memory),
+            [
+                "free_bytes",
+                "modified_bytes",
+                "page_size",
+                "standby_by_priority_bytes",
+                "standby_bytes",
+                "total_bytes"
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&values.install).unwrap(),
+            json!({"installed": false, "install_dir": null, "global_timer_requests": "disabled"})
+        );
+
+        assert_eq!(fields(&ProbeReport::new(&[])), ["achievable_hns"]);
+        assert_eq!(
+            fields(&BenchReport::new(&[], None)),
+            ["recommended_hns", "results"]
+        );
+        assert_eq!(
+            fields(&BenchEntry {
+                requested_hns: 0,
+                applied_hns: 0,
+                samples: 0,
+                mean_ns: 0,
+                p50_ns: 0,
+                p99_ns: 0,
+                max_ns: 0,
+            }),
+            [
+                "applied_hns",
+                "max_ns",
+                "mean_ns",
+                "p50_ns",
+                "p99_ns",
+                "requested_hns",
+                "samples"
+            ]
+        );
+        assert_eq!(
+            fields(&ResetReport {
+                stopped: vec![],
+                cur_hns: 0
+            }),
+            ["cur_hns", "stopped"]
+        );
+        assert_eq!(
+            fields(&SimulateReport::new(0, &[]).unwrap()),
+            ["purges", "samples", "total_reclaimed_bytes"]
+        );
+        assert_eq!(
+            fields(&PurgeEntry {
+                timestamp: String::new(),
+                reason: String::new(),
+                reclaimed_bytes: 0,
+                free_before_bytes: 0,
+                free_after_bytes: 0,
+                standby_before_bytes: 0,
+                standby_after_bytes: 0,
+            }),
+            [
+                "free_after_bytes",
+                "free_before_bytes",
+                "reason",
+                "reclaimed_bytes",
+                "standby_after_bytes",
+                "standby_before_bytes",
+                "timestamp"
+            ]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// What made timerset shut down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownReason {
+    /// Ctrl+C, or SIGINT
+    Interrupt,
+    /// SIGTERM, the console window getting closed, or `reset`
+    Terminate,
+    /// The user session is ending
+    Logoff,
+    /// The system is shutting down
+    #[cfg_attr(not(windows), allow(dead_code))]
+    SystemShutdown,
+}
+
+impl std::fmt::Display for ShutdownReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Interrupt => write!(f, "interrupted"),
+            Self::Terminate => write!(f, "terminated"),
+            Self::Logoff => write!(f, "session ending"),
+            Self::SystemShutdown => write!(f, "system shutting down"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ShutdownState {
+    requested: Option<ShutdownReason>,
+    completed: bool,
+}
+
+/// Shutdown coordinator, shared between the platform event handlers that request shutdowns
+/// and the loops that have to stop and clean up before the process exits.
+///
+/// Some events (console close, logoff) terminate the process as soon as their handler returns,
+/// so handlers wait for `complete` to be called, with a timeout.
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown(Arc<(Mutex<ShutdownState>, Condvar)>);
+
+impl Shutdown {
+    fn state(&self) -> MutexGuard<'_, ShutdownState> {
+        self.0 .0.lock().unwrap()
+    }
+
+    /// Requests a shutdown; only the first reason is kept
+    pub fn request(&self, reason: ShutdownReason) {
+        let mut state = self.state();
+        if state.requested.is_none() {
+            state.requested = Some(reason);
+        }
+        self.0 .1.notify_all();
+    }
+
+    pub fn requested(&self) -> Option<ShutdownReason> {
+        self.state().requested
+    }
+
+    /// Blocks until a shutdown is requested
+    pub fn wait(&self) -> ShutdownReason {
+        let state = self
+            .0
+             .1
+            .wait_while(self.state(), |state| state.requested.is_none())
+            .unwrap();
+        state.requested.unwrap()
+    }
+
+    /// Blocks until a shutdown is requested, or `timeout` elapsed
+    pub fn wait_timeout(&self, timeout: Duration) -> Option<ShutdownReason> {
+        let (state, _) = self
+            .0
+             .1
+            .wait_timeout_while(self.state(), timeout, |state| state.requested.is_none())
+            .unwrap();
+        state.requested
+    }
+
+    /// Signals that cleaning up is done and the process can exit
+    pub fn complete(&self) {
+        self.state().completed = true;
+        self.0 .1.notify_all();
+    }
+
+    /// Blocks until cleaning up is done, or `timeout` elapsed. Returns whether it is done.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub fn wait_completed(&self, timeout: Duration) -> bool {
+        let (state, _) = self
+            .0
+             .1
+            .wait_timeout_while(self.state(), timeout, |state| !state.completed)
+            .unwrap();
+        state.completed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{Shutdown, ShutdownReason};
+    use crate::platform::sim::{SimMemory, SimNotification, SimTimer};
+    use crate::standby::StandbyListCleaner;
+    use crate::timer::{TimerResolution, TimerResolutionInfo, TimerWatchdog};
+
+    #[test]
+    fn should_keep_the_first_reason() {
+        let shutdown = Shutdown::default();
+        assert_eq!(shutdown.wait_timeout(Duration::from_millis(1)), None);
+        shutdown.request(ShutdownReason::Interrupt);
+        shutdown.request(ShutdownReason::Logoff);
+        assert_eq!(shutdown.wait(), ShutdownReason::Interrupt);
+        assert!(!shutdown.wait_completed(Duration::from_millis(1)));
+        shutdown.complete();
+        assert!(shutdown.wait_completed(Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn should_stop_and_clean_up_on_shutdown_request() {
+        let shutdown = Shutdown::default();
+
+        let timer = SimTimer::new(156250, 5000, 156250);
+        let mut info = TimerResolutionInfo::fetch_with(timer.clone()).unwrap();
+        info.apply_timer(TimerResolution::from_millis(1)).unwrap();
+        let mut watchdog = TimerWatchdog::new(info, TimerResolution::from_millis(1)).interval(1);
+        let watchdog = {
+            let shutdown = shutdown.clone();
+            std::thread::spawn(move || {
+                watchdog.run(&shutdown);
+                watchdog.into_info()
+            })
+        };
+
+        let notification = SimNotification::default();
+        let mut cleaner = StandbyListCleaner::with_backends(
+            SimMemory::new(2_000_000_000, 3_000_000_000),
+            notification.clone(),
+        )
+        .poll_interval(0);
+        let cleaner = {
+            let shutdown = shutdown.clone();
+            std::thread::spawn(move || cleaner.monitor_and_clean(&shutdown))
+        };
+
+        // Wait for the cleaner to be up and running, then request a shutdown the way platform handlers do
+        while notification.state().waits == 0 {
+            std::thread::yield_now();
+        }
+        assert!(notification.state().armed.is_some());
+        let handler = {
+            let shutdown = shutdown.clone();
+            std::thread::spawn(move || {
+                shutdown.request(ShutdownReason::Terminate);
+                shutdown.wait_completed(Duration::from_secs(5))
+            })
+        };
+
+        assert_eq!(shutdown.wait(), ShutdownReason::Terminate);
+        cleaner.join().unwrap().unwrap();
+        assert_eq!(notification.state().armed, None);
+        let mut info = watchdog.join().unwrap();
+        info.release_timer().unwrap();
+        assert_eq!(
+            timer.state().releases,
+            vec![TimerResolution::from_millis(1)]
+        );
+
+        shutdown.complete();
+        assert!(handler.join().unwrap());
+    }
+}
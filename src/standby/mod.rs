@@ -0,0 +1,952 @@
+mod mode;
+pub use self::mode::*;
+
+mod policy;
+pub use self::policy::*;
+
+mod priority;
+pub use self::priority::*;
+
+mod recorder;
+pub use self::recorder::*;
+
+mod simulate;
+pub use self::simulate::*;
+
+mod stats;
+pub use self::stats::*;
+
+mod strategy;
+pub use self::strategy::*;
+
+mod threshold;
+pub use self::threshold::*;
+
+use log::{debug, info, warn};
+
+use crate::platform::{
+    MemoryBackend, MemoryListInfo, MemoryNotification, NativeMemory, NativeNotification,
+    PowerState, PurgeCommand,
+};
+use crate::power::PowerWatcher;
+use crate::process::{ProcessEvent, ProcessWatcher};
+use crate::shutdown::Shutdown;
+
+/// Longest time spent waiting on a memory notification before checking for shutdown requests and watched processes
+const SHUTDOWN_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+#[derive(Debug)]
+pub struct StandbyListCleaner<
+    M: MemoryBackend = NativeMemory,
+    N: MemoryNotification = NativeNotification,
+> {
+    standbylist_threshold_config: MemoryThreshold,
+    freemem_threshold_config: MemoryThreshold,
+    /// Thresholds in bytes, as resolved by `resolve_thresholds`
+    standbylist_threshold: usize,
+    freemem_threshold: usize,
+    /// Thresholds applying while a watched process runs, defaulting to the ones above
+    process_standbylist_threshold_config: Option<MemoryThreshold>,
+    process_freemem_threshold_config: Option<MemoryThreshold>,
+    process_standbylist_threshold: usize,
+    process_freemem_threshold: usize,
+    /// Standby list priorities counted against the threshold
+    priorities: PriorityRange,
+    purge_cooldown: std::time::Duration,
+    rearm_threshold_config: Option<MemoryThreshold>,
+    max_purges_per_hour: Option<usize>,
+    /// Built by `resolve_thresholds`
+    policy: PurgePolicy,
+    strategy: PurgeStrategy,
+    poll_freq: std::time::Duration,
+    last_memory_wait: Option<std::time::Instant>,
+    stats: PurgeStats,
+    recorder: Option<MemoryRecorder>,
+    processes: Option<ProcessWatcher>,
+    /// Watched process that started since the last purge, if any
+    launched: Option<String>,
+    /// Cleaning is paused while this reports running on battery
+    power: Option<PowerWatcher>,
+    memory: M,
+    notification: N,
+}
+
+impl Default for StandbyListCleaner {
+    fn default() -> Self {
+        Self::with_backends(Default::default(), Default::default())
+    }
+}
+
+impl<M: MemoryBackend, N: MemoryNotification> Drop for StandbyListCleaner<M, N> {
+    fn drop(&mut self) {
+        let _ = self.notification.disarm();
+    }
+}
+
+impl<M: MemoryBackend, N: MemoryNotification> StandbyListCleaner<M, N> {
+    pub fn with_backends(memory: M, notification: N) -> Self {
+        Self {
+            standbylist_threshold_config: MemoryThreshold::default(),
+            freemem_threshold_config: MemoryThreshold::default(),
+            standbylist_threshold: 0,
+            freemem_threshold: 0,
+            process_standbylist_threshold_config: None,
+            process_freemem_threshold_config: None,
+            process_standbylist_threshold: 0,
+            process_freemem_threshold: 0,
+            priorities: PriorityRange::default(),
+            purge_cooldown: std::time::Duration::ZERO,
+            rearm_threshold_config: None,
+            max_purges_per_hour: None,
+            policy: PurgePolicy::new(0, 0),
+            strategy: PurgeStrategy::default(),
+            poll_freq: std::time::Duration::from_secs(10),
+            last_memory_wait: None,
+            stats: PurgeStats::default(),
+            recorder: None,
+            processes: None,
+            launched: None,
+            power: None,
+            memory,
+            notification,
+        }
+    }
+
+    /// Standby List size threshold, resolved against the total physical memory when monitoring starts
+    pub fn standby_list_size_threshold(mut self, threshold: MemoryThreshold) -> Self {
+        self.standbylist_threshold_config = threshold;
+        self
+    }
+
+    /// Free memory threshold, resolved against the total physical memory when monitoring starts
+    pub fn free_memory_size_threshold(mut self, threshold: MemoryThreshold) -> Self {
+        self.freemem_threshold_config = threshold;
+        self
+    }
+
+    /// Standby List priorities counted against the Standby List size threshold.
+    /// When restricted, the full Standby List purge step is narrowed down to a low priority purge
+    /// whenever priority 0 pages alone exceed the threshold.
+    pub fn standby_priorities(mut self, priorities: PriorityRange) -> Self {
+        self.priorities = priorities;
+        self
+    }
+
+    /// Minimum time between two purges, in seconds
+    pub fn purge_cooldown(mut self, cooldown: u64) -> Self {
+        self.purge_cooldown = std::time::Duration::from_secs(cooldown);
+        self
+    }
+
+    /// Free memory threshold that has to be reached again after a purge before the next one
+    pub fn rearm_threshold(mut self, threshold: Option<MemoryThreshold>) -> Self {
+        self.rearm_threshold_config = threshold;
+        self
+    }
+
+    /// Maximum number of purges within any hour
+    pub fn max_purges_per_hour(mut self, max: Option<usize>) -> Self {
+        self.max_purges_per_hour = max;
+        self
+    }
+
+    /// Memory list commands issued when the cleaning conditions are met
+    pub fn purge_strategy(mut self, strategy: PurgeStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Configurable poll interval in seconds, at least 1: polling sources never wait on their own
+    pub fn poll_interval(mut self, interval: u64) -> Self {
+        self.poll_freq = std::time::Duration::from_secs(interval.max(1));
+        self
+    }
+
+    /// Records a memory list sample on each wakeup and after each purge
+    pub fn record_to(mut self, recorder: MemoryRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Purges as soon as a watched process starts, on top of the memory thresholds.
+    /// Processes are checked whenever the cleaner wakes up, and at least every second.
+    pub fn watch_processes(mut self, watcher: ProcessWatcher) -> Self {
+        self.processes = Some(watcher);
+        self
+    }
+
+    /// Pauses cleaning while running on battery: purges cost CPU time and disk reads down the line
+    pub fn pause_on_battery(mut self, power: PowerWatcher) -> Self {
+        self.power = Some(power);
+        self
+    }
+
+    /// Standby List size threshold applying while a watched process runs
+    pub fn process_standby_list_size_threshold(
+        mut self,
+        threshold: Option<MemoryThreshold>,
+    ) -> Self {
+        self.process_standbylist_threshold_config = threshold;
+        self
+    }
+
+    /// Free memory threshold applying while a watched process runs
+    pub fn process_free_memory_size_threshold(
+        mut self,
+        threshold: Option<MemoryThreshold>,
+    ) -> Self {
+        self.process_freemem_threshold_config = threshold;
+        self
+    }
+
+    /// Purge totals and history since the cleaner was created
+    pub fn stats(&self) -> &PurgeStats {
+        &self.stats
+    }
+
+    /// Starts the monitoring loop.
+    /// Note that this is a blocking function that runs until a shutdown is requested or there's an error.
+    pub fn monitor_and_clean(&mut self, shutdown: &Shutdown) -> crate::TimersetResult<()> {
+        self.resolve_thresholds()?;
+        self.check_strategy()?;
+        self.memory.acquire_privileges()?;
+        self.notification.arm(self.freemem_threshold)?;
+        self.check_processes()?;
+
+        while shutdown.requested().is_none() {
+            self.clean_if_needed()?;
+
+            debug!("Waiting on memory notification now...");
+            self.wait_on_notification(shutdown)?;
+        }
+
+        debug!("Stopping memory monitoring");
+        self.notification.disarm()
+    }
+
+    /// Fails when no step of the purge strategy can run on this platform
+    fn check_strategy(&self) -> crate::TimersetResult<()> {
+        if !self
+            .strategy
+            .steps()
+            .iter()
+            .any(|&command| self.memory.supports(command))
+        {
+            return Err(eyre::eyre!(
+                "None of the purge steps of {} are supported on this platform",
+                self.strategy
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Resolves the configured thresholds in bytes against the total physical memory
+    pub fn resolve_thresholds(&mut self) -> crate::TimersetResult<()> {
+        let total = self.memory.total_physical_memory()?;
+        self.standbylist_threshold = self.standbylist_threshold_config.resolve(total);
+        self.freemem_threshold = self.freemem_threshold_config.resolve(total);
+        info!(
+            "Total physical memory: {} bytes; Standby List threshold: {} = {} bytes; Free memory threshold: {} = {} bytes",
+            total,
+            self.standbylist_threshold_config,
+            self.standbylist_threshold,
+            self.freemem_threshold_config,
+            self.freemem_threshold
+        );
+
+        self.process_standbylist_threshold = self
+            .process_standbylist_threshold_config
+            .map_or(self.standbylist_threshold, |t| t.resolve(total));
+        self.process_freemem_threshold = self
+            .process_freemem_threshold_config
+            .map_or(self.freemem_threshold, |t| t.resolve(total));
+        if self.processes.is_some() {
+            info!(
+                "While watched processes run: Standby List threshold: {} bytes; Free memory threshold: {} bytes",
+                self.process_standbylist_threshold, self.process_freemem_threshold
+            );
+        }
+
+        let rearm_threshold = self.rearm_threshold_config.map(|t| t.resolve(total));
+        if let (Some(config), Some(threshold)) = (self.rearm_threshold_config, rearm_threshold) {
+            info!("Re-arm threshold: {} = {} bytes", config, threshold);
+        }
+        self.policy = PurgePolicy::new(
+            self.active_freemem_threshold(),
+            self.active_standbylist_threshold(),
+        )
+        .min_interval(self.purge_cooldown)
+        .rearm_threshold(rearm_threshold)
+        .max_per_hour(self.max_purges_per_hour);
+        Ok(())
+    }
+
+    /// Whether a watched process is running
+    fn process_running(&self) -> bool {
+        self.processes.as_ref().is_some_and(ProcessWatcher::active)
+    }
+
+    /// Free memory threshold in bytes, depending on whether a watched process is running
+    fn active_freemem_threshold(&self) -> usize {
+        if self.process_running() {
+            self.process_freemem_threshold
+        } else {
+            self.freemem_threshold
+        }
+    }
+
+    /// Standby List size threshold in bytes, depending on whether a watched process is running
+    fn active_standbylist_threshold(&self) -> usize {
+        if self.process_running() {
+            self.process_standbylist_threshold
+        } else {
+            self.standbylist_threshold
+        }
+    }
+
+    /// Lists the running processes, if any are watched, and switches thresholds when watched processes
+    /// start or exit. Returns whether a watched process started since the last purge.
+    /// Failing to list processes doesn't stop the cleaner.
+    pub(crate) fn check_processes(&mut self) -> crate::TimersetResult<bool> {
+        let Some(watcher) = self.processes.as_mut() else {
+            return Ok(false);
+        };
+        let was_running = watcher.active();
+        let events = match watcher.poll() {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("Could not list running processes: {}", e);
+                return Ok(self.launched.is_some());
+            }
+        };
+
+        for event in events {
+            match event {
+                ProcessEvent::Started(name) => {
+                    info!("Watched process {} started", name);
+                    self.launched.get_or_insert(name);
+                }
+                ProcessEvent::Exited(name) => info!("Watched process {} exited", name),
+            }
+        }
+
+        if self.process_running() != was_running {
+            let (freemem, standbylist) = (
+                self.active_freemem_threshold(),
+                self.active_standbylist_threshold(),
+            );
+            debug!(
+                "Switching to thresholds: free memory {} bytes / Standby List {} bytes",
+                freemem, standbylist
+            );
+            self.policy.set_thresholds(freemem, standbylist);
+            if self.process_freemem_threshold != self.freemem_threshold {
+                // Notification sources such as CMRN are configured with the free memory threshold
+                self.notification.disarm()?;
+                self.notification.arm(freemem)?;
+            }
+        }
+
+        Ok(self.launched.is_some())
+    }
+
+    /// Whether cleaning is paused because the system runs on battery.
+    /// Failing to read the power source doesn't stop the cleaner.
+    fn paused_on_battery(&mut self) -> bool {
+        let Some(power) = self.power.as_mut() else {
+            return false;
+        };
+        match power.poll() {
+            Ok(Some(PowerState::Battery)) => {
+                info!("Pausing Standby List cleaning while on battery")
+            }
+            Ok(Some(PowerState::Ac)) => debug!("Standby List cleaning is active on AC power"),
+            Ok(None) => {}
+            Err(e) => warn!("Could not read the power source: {}", e),
+        }
+        power.state() == PowerState::Battery
+    }
+
+    /// Queries the memory lists once and purges the standby list if a watched process started,
+    /// or if the purge policy allows it. Launch purges skip the thresholds, but not the cooldown
+    /// nor the hourly cap. Nothing gets purged while paused on battery.
+    /// Returns whether a purge happened.
+    pub(crate) fn clean_if_needed(&mut self) -> crate::TimersetResult<bool> {
+        let page_size = self.memory.page_size();
+        let lists = self.memory.query_memory_lists()?;
+        self.record_sample(RecordEvent::Poll, &lists);
+
+        if self.paused_on_battery() {
+            self.launched = None;
+            return Ok(false);
+        }
+
+        // Launch purges held off by the cooldown or the hourly cap stay pending until allowed
+        if let Some(name) = &self.launched {
+            match self.policy.evaluate_limits(std::time::Instant::now()) {
+                PurgeDecision::Purge => {}
+                decision => {
+                    info!("{} started, but holding off purging: {:?}", name, decision);
+                    return Ok(false);
+                }
+            }
+        }
+
+        if let Some(name) = self.launched.take() {
+            info!("{} started, now freeing standby list", name);
+            let Some(record) =
+                self.run_purge_strategy(PurgeReason::ProcessStarted { name }, lists)?
+            else {
+                return Ok(false);
+            };
+            self.policy.record_purge(std::time::Instant::now());
+            info!("{}", record);
+            self.stats.record(record);
+            return Ok(true);
+        }
+
+        let snapshot = self.snapshot(std::time::Instant::now(), &lists);
+        let breakdown = PriorityBreakdown {
+            lists: &lists,
+            page_size,
+        };
+        debug!("Free memory: {:.2}MB", snapshot.free as f64 / 1_000_000.0);
+        debug!(
+            "Standby List memory (priorities {}): {:.2}MB [{}]",
+            self.priorities,
+            snapshot.standby as f64 / 1_000_000.0,
+            breakdown
+        );
+
+        match self.policy.evaluate(&snapshot) {
+            PurgeDecision::Purge => {}
+            PurgeDecision::BelowThresholds => return Ok(false),
+            decision => {
+                info!("Conditions met, but holding off purging: {:?}", decision);
+                return Ok(false);
+            }
+        }
+
+        info!("Conditions met, now freeing standby list [{}]", breakdown);
+        let reason = PurgeReason::Thresholds {
+            free_threshold: self.active_freemem_threshold(),
+            standby_threshold: self.active_standbylist_threshold(),
+        };
+        let Some(record) = self.run_purge_strategy(reason, lists)? else {
+            return Ok(false);
+        };
+        self.policy.record_purge(std::time::Instant::now());
+        info!("{}", record);
+        self.stats.record(record);
+        debug!("Standby list cleaned up");
+        Ok(true)
+    }
+
+    /// Free memory and Standby List size within the targeted priorities, in bytes
+    fn snapshot(&self, at: std::time::Instant, lists: &MemoryListInfo) -> MemorySnapshot {
+        // Standby list and free memory are counted in pages;
+        // multiplying by the page size -usually 4KB- gets the value in bytes
+        let page_size = self.memory.page_size();
+        MemorySnapshot {
+            at,
+            free: lists.free_pages() * page_size,
+            standby: self.priorities.standby_pages(lists) * page_size,
+        }
+    }
+
+    /// Runs the purge strategy steps in order, re-querying the memory lists after each of them.
+    /// Stops as soon as free memory went back above the threshold.
+    /// Returns `None` when no step could run, in which case nothing counts as a purge.
+    fn run_purge_strategy(
+        &mut self,
+        reason: PurgeReason,
+        mut lists: MemoryListInfo,
+    ) -> crate::TimersetResult<Option<PurgeRecord>> {
+        let page_size = self.memory.page_size();
+        let mut record = PurgeRecord {
+            timestamp: time::OffsetDateTime::now_utc(),
+            reason,
+            steps: vec![],
+            free_before: lists.free_pages() * page_size,
+            free_after: lists.free_pages() * page_size,
+            standby_before: lists.standby_pages() * page_size,
+            standby_after: lists.standby_pages() * page_size,
+            blocked: std::time::Duration::ZERO,
+        };
+
+        for (i, &command) in self.strategy.steps().iter().enumerate() {
+            if i > 0 && lists.free_pages() * page_size >= self.active_freemem_threshold() {
+                debug!("Free memory is back above the threshold, skipping remaining purge steps");
+                break;
+            }
+
+            let command = match command {
+                PurgeCommand::PurgeStandbyList
+                    if self.priorities != PriorityRange::default()
+                        && self
+                            .memory
+                            .supports(PurgeCommand::PurgeLowPriorityStandbyList)
+                        && lists.page_count_by_priority[0] * page_size
+                            > self.active_standbylist_threshold() =>
+                {
+                    info!("Priority 0 pages alone exceed the Standby List threshold, only purging those");
+                    PurgeCommand::PurgeLowPriorityStandbyList
+                }
+                command => command,
+            };
+
+            let start = std::time::Instant::now();
+            let result = self.memory.purge(command);
+            record.blocked += start.elapsed();
+            match result {
+                Err(crate::TimersetError::IoError(e))
+                    if e.kind() == std::io::ErrorKind::Unsupported =>
+                {
+                    warn!(
+                        "Purge step {} is not supported on this platform, skipping",
+                        command
+                    );
+                    continue;
+                }
+                result => result?,
+            }
+            record.steps.push(command);
+
+            let after = self.memory.query_memory_lists()?;
+            let free_before = lists.free_pages() * page_size;
+            let free_after = after.free_pages() * page_size;
+            info!(
+                "Purge step {}: reclaimed {}MB (free: {}MB -> {}MB / standby: {}MB -> {}MB)",
+                command,
+                free_after.saturating_sub(free_before) / 1_000_000,
+                free_before / 1_000_000,
+                free_after / 1_000_000,
+                lists.standby_pages() * page_size / 1_000_000,
+                after.standby_pages() * page_size / 1_000_000,
+            );
+            lists = after;
+        }
+
+        if record.steps.is_empty() {
+            warn!("No purge step could run, nothing was purged");
+            return Ok(None);
+        }
+
+        record.free_after = lists.free_pages() * page_size;
+        record.standby_after = lists.standby_pages() * page_size;
+        self.record_sample(
+            RecordEvent::Purge {
+                reclaimed: record.reclaimed(),
+            },
+            &lists,
+        );
+        Ok(Some(record))
+    }
+
+    /// Appends a sample to the recording, if any. Failing to record doesn't stop the cleaner.
+    fn record_sample(&mut self, event: RecordEvent, lists: &MemoryListInfo) {
+        let page_size = self.memory.page_size();
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(e) =
+                recorder.record(time::OffsetDateTime::now_utc(), event, page_size, lists)
+            {
+                warn!("Could not record memory sample: {}", e);
+            }
+        }
+    }
+
+    /// Waits for the next memory notification, no sooner than `poll_freq` after the previous one.
+    /// Notification sources such as CMRN can fire thousands of times per second under memory pressure.
+    /// Returns early when a shutdown is requested or a watched process started.
+    fn wait_on_notification(&mut self, shutdown: &Shutdown) -> crate::TimersetResult<()> {
+        if let Some(elapsed) = self
+            .last_memory_wait
+            .as_ref()
+            .map(std::time::Instant::elapsed)
+        {
+            if elapsed < self.poll_freq {
+                let sleep_dur = self.poll_freq - elapsed;
+                debug!(
+                    "Anti Kernel-DOS triggered, sleeping {}s",
+                    sleep_dur.as_secs()
+                );
+                let deadline = std::time::Instant::now() + sleep_dur;
+                loop {
+                    let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                    if remaining.is_zero() {
+                        break;
+                    }
+                    if shutdown
+                        .wait_timeout(remaining.min(SHUTDOWN_CHECK_INTERVAL))
+                        .is_some()
+                        || self.check_processes()?
+                    {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        self.last_memory_wait = Some(std::time::Instant::now());
+        // Notifications are waited on in slices so that shutdown requests and watched processes get noticed
+        while !self.notification.wait(SHUTDOWN_CHECK_INTERVAL)? {
+            if shutdown.requested().is_some() || self.check_processes()? {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        MemoryRecorder, MemoryThreshold, ProcessWatcher, PurgeReason, Shutdown, StandbyListCleaner,
+    };
+    use crate::platform::sim::{SimMemory, SimNotification, SimPower, SimProcesses};
+    use crate::platform::{MemoryNotification as _, PollingNotification, PowerState, PurgeCommand};
+    use crate::power::PowerWatcher;
+    use crate::shutdown::ShutdownReason;
+
+    #[test]
+    fn should_purge_only_when_thresholds_are_met() {
+        let memory = SimMemory::new(2_000_000_000, 3_000_000_000);
+        let mut cleaner =
+            StandbyListCleaner::with_backends(memory.clone(), SimNotification::default())
+                .standby_list_size_threshold(MemoryThreshold::default())
+                .free_memory_size_threshold(MemoryThreshold::default());
+        cleaner.resolve_thresholds().unwrap();
+
+        // Plenty of free memory
+        assert!(!cleaner.clean_if_needed().unwrap());
+
+        // Low free memory but tiny standby list
+        memory.set_memory(500_000_000, 500_000_000);
+        assert!(!cleaner.clean_if_needed().unwrap());
+
+        // Low free memory and big standby list
+        memory.set_memory(500_000_000, 3_000_000_000);
+        assert!(cleaner.clean_if_needed().unwrap());
+        assert_eq!(memory.state().purges, [PurgeCommand::PurgeStandbyList]);
+        assert_eq!(memory.state().lists.standby_pages(), 0);
+
+        // Standby list got reclaimed as free memory
+        assert!(!cleaner.clean_if_needed().unwrap());
+        assert_eq!(memory.state().purges.len(), 1);
+
+        let stats = cleaner.stats();
+        assert_eq!(stats.purges, 1);
+        let record = stats.history().next_back().unwrap();
+        assert_eq!(record.steps, [PurgeCommand::PurgeStandbyList]);
+        assert_eq!(record.free_before, 500_000_000 / 4096 * 4096);
+        assert_eq!(record.standby_before, 3_000_000_000 / 4096 * 4096);
+        assert_eq!(record.standby_after, 0);
+        assert_eq!(record.reclaimed(), 3_000_000_000 / 4096 * 4096);
+        assert_eq!(stats.total_reclaimed, record.reclaimed() as u64);
+    }
+
+    #[test]
+    fn should_record_polls_and_purges() {
+        let path = std::env::temp_dir().join(format!("timerset-record-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let memory = SimMemory::new(500_000_000, 3_000_000_000);
+        let mut cleaner =
+            StandbyListCleaner::with_backends(memory.clone(), SimNotification::default())
+                .record_to(MemoryRecorder::open(&path).unwrap());
+        cleaner.resolve_thresholds().unwrap();
+        assert!(cleaner.clean_if_needed().unwrap());
+        assert!(!cleaner.clean_if_needed().unwrap());
+        drop(cleaner);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let events: Vec<_> = contents
+            .lines()
+            .map(|line| line.split(',').nth(1).unwrap())
+            .collect();
+        assert_eq!(events, ["event", "poll", "purge", "poll"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_purge_on_launch_and_switch_thresholds_while_running() {
+        let memory = SimMemory::new(2_000_000_000, 500_000_000);
+        let notification = SimNotification::default();
+        let processes = SimProcesses::default();
+        let mut cleaner = StandbyListCleaner::with_backends(memory.clone(), notification.clone())
+            .free_memory_size_threshold("1GB".parse().unwrap())
+            .process_free_memory_size_threshold(Some("4GB".parse().unwrap()))
+            .process_standby_list_size_threshold(Some("100MB".parse().unwrap()))
+            .watch_processes(ProcessWatcher::new(
+                "game.exe".parse().unwrap(),
+                processes.clone(),
+            ));
+        cleaner.resolve_thresholds().unwrap();
+        assert!(!cleaner.check_processes().unwrap());
+        assert!(!cleaner.clean_if_needed().unwrap());
+
+        // The launch purges right away, whatever the thresholds
+        processes.set_running(&["explorer.exe", "Game.exe"]);
+        assert!(cleaner.check_processes().unwrap());
+        assert_eq!(notification.state().armed, Some(4_000_000_000));
+        assert!(cleaner.clean_if_needed().unwrap());
+        assert_eq!(
+            cleaner.stats().history().next_back().unwrap().reason,
+            PurgeReason::ProcessStarted {
+                name: "Game.exe".into()
+            }
+        );
+        assert!(!cleaner.check_processes().unwrap());
+
+        // Then the process thresholds apply while it runs
+        memory.set_memory(2_000_000_000, 500_000_000);
+        assert!(cleaner.clean_if_needed().unwrap());
+        assert_eq!(memory.state().purges.len(), 2);
+
+        processes.set_running(&["explorer.exe"]);
+        assert!(!cleaner.check_processes().unwrap());
+        assert_eq!(notification.state().armed, Some(1_000_000_000));
+        memory.set_memory(2_000_000_000, 500_000_000);
+        assert!(!cleaner.clean_if_needed().unwrap());
+    }
+
+    #[test]
+    fn should_rate_limit_launch_purges() {
+        let memory = SimMemory::new(2_000_000_000, 500_000_000);
+        let processes = SimProcesses::default();
+        let mut cleaner =
+            StandbyListCleaner::with_backends(memory.clone(), SimNotification::default())
+                .purge_cooldown(60)
+                .max_purges_per_hour(Some(1))
+                .watch_processes(ProcessWatcher::new(
+                    "game.exe".parse().unwrap(),
+                    processes.clone(),
+                ));
+        cleaner.resolve_thresholds().unwrap();
+
+        processes.set_running(&["Game.exe"]);
+        assert!(cleaner.check_processes().unwrap());
+        assert!(cleaner.clean_if_needed().unwrap());
+
+        // Restarting the game right away doesn't purge again
+        processes.set_running(&[]);
+        assert!(!cleaner.check_processes().unwrap());
+        processes.set_running(&["Game.exe"]);
+        assert!(cleaner.check_processes().unwrap());
+        assert!(!cleaner.clean_if_needed().unwrap());
+        assert_eq!(memory.state().purges.len(), 1);
+        assert_eq!(cleaner.stats().purges, 1);
+    }
+
+    #[test]
+    fn should_defer_held_off_launch_purges() {
+        let memory = SimMemory::new(2_000_000_000, 500_000_000);
+        let processes = SimProcesses::default();
+        let mut cleaner =
+            StandbyListCleaner::with_backends(memory.clone(), SimNotification::default())
+                .purge_cooldown(60)
+                .watch_processes(ProcessWatcher::new(
+                    "game.exe".parse().unwrap(),
+                    processes.clone(),
+                ));
+        cleaner.resolve_thresholds().unwrap();
+
+        processes.set_running(&["Game.exe"]);
+        assert!(cleaner.check_processes().unwrap());
+        assert!(cleaner.clean_if_needed().unwrap());
+        processes.set_running(&[]);
+        cleaner.check_processes().unwrap();
+        processes.set_running(&["Game.exe"]);
+        assert!(cleaner.check_processes().unwrap());
+        assert!(!cleaner.clean_if_needed().unwrap());
+
+        // Once the cooldown is over, the pending launch purge goes through
+        cleaner.policy = cleaner
+            .policy
+            .clone()
+            .min_interval(std::time::Duration::ZERO);
+        assert!(cleaner.check_processes().unwrap());
+        assert!(cleaner.clean_if_needed().unwrap());
+        assert!(!cleaner.clean_if_needed().unwrap());
+        assert_eq!(memory.state().purges.len(), 2);
+        assert_eq!(cleaner.stats().purges, 2);
+    }
+
+    #[test]
+    fn should_pause_on_battery() {
+        let memory = SimMemory::new(500_000_000, 3_000_000_000);
+        let power = SimPower::default();
+        let mut cleaner =
+            StandbyListCleaner::with_backends(memory.clone(), SimNotification::default())
+                .pause_on_battery(PowerWatcher::new(power.clone()));
+        cleaner.resolve_thresholds().unwrap();
+
+        power.set(PowerState::Battery);
+        assert!(!cleaner.clean_if_needed().unwrap());
+        assert!(memory.state().purges.is_empty());
+
+        power.set(PowerState::Ac);
+        assert!(cleaner.clean_if_needed().unwrap());
+        assert_eq!(memory.state().purges, [PurgeCommand::PurgeStandbyList]);
+    }
+
+    #[test]
+    fn should_only_count_targeted_priorities() {
+        let memory = SimMemory::new(500_000_000, 0);
+        let mut cleaner =
+            StandbyListCleaner::with_backends(memory.clone(), SimNotification::default())
+                .standby_list_size_threshold("1GB".parse().unwrap())
+                .standby_priorities("0-4".parse().unwrap());
+        cleaner.resolve_thresholds().unwrap();
+
+        // High priority pages don't count
+        memory.state().lists.page_count_by_priority[5] = 3_000_000_000 / 4096;
+        assert!(!cleaner.clean_if_needed().unwrap());
+
+        // Low priority pages alone exceed the threshold: only those get purged
+        memory.state().lists.page_count_by_priority[0] = 2_000_000_000 / 4096;
+        assert!(cleaner.clean_if_needed().unwrap());
+        assert_eq!(
+            memory.state().purges,
+            [PurgeCommand::PurgeLowPriorityStandbyList]
+        );
+
+        // Priority 0 alone doesn't, the whole list does
+        memory.state().lists.page_count_by_priority[0] = 500_000_000 / 4096;
+        memory.state().lists.page_count_by_priority[3] = 1_000_000_000 / 4096;
+        memory.state().lists.free_page_count = 500_000_000 / 4096;
+        assert!(cleaner.clean_if_needed().unwrap());
+        assert_eq!(memory.state().purges[1], PurgeCommand::PurgeStandbyList);
+        assert_eq!(memory.state().lists.standby_pages(), 0);
+    }
+
+    #[test]
+    fn should_stop_purge_strategy_once_free_memory_recovers() {
+        let memory = SimMemory::new(500_000_000, 0);
+        let mut cleaner =
+            StandbyListCleaner::with_backends(memory.clone(), SimNotification::default())
+                .standby_list_size_threshold("1GB".parse().unwrap())
+                .free_memory_size_threshold("1GB".parse().unwrap())
+                .purge_strategy("low-standby,standby".parse().unwrap());
+        cleaner.resolve_thresholds().unwrap();
+
+        // Purging the low priority standby list is enough
+        memory.state().lists.page_count_by_priority[0] = 1_000_000_000 / 4096;
+        memory.state().lists.page_count_by_priority[5] = 1_000_000_000 / 4096;
+        assert!(cleaner.clean_if_needed().unwrap());
+        assert_eq!(
+            memory.state().purges,
+            [PurgeCommand::PurgeLowPriorityStandbyList]
+        );
+
+        // Not enough low priority pages, the full standby list has to go
+        memory.state().purges.clear();
+        memory.set_memory(500_000_000, 0);
+        memory.state().lists.page_count_by_priority[0] = 100_000_000 / 4096;
+        memory.state().lists.page_count_by_priority[5] = 1_000_000_000 / 4096;
+        assert!(cleaner.clean_if_needed().unwrap());
+        assert_eq!(
+            memory.state().purges,
+            [
+                PurgeCommand::PurgeLowPriorityStandbyList,
+                PurgeCommand::PurgeStandbyList
+            ]
+        );
+        assert_eq!(memory.state().lists.standby_pages(), 0);
+    }
+
+    #[test]
+    fn should_skip_unsupported_purge_steps() {
+        let memory = SimMemory::new(500_000_000, 3_000_000_000);
+        memory.state().unsupported = vec![
+            PurgeCommand::EmptyWorkingSets,
+            PurgeCommand::PurgeLowPriorityStandbyList,
+        ];
+        let mut cleaner =
+            StandbyListCleaner::with_backends(memory.clone(), SimNotification::default())
+                .purge_strategy("working-sets".parse().unwrap());
+        assert!(cleaner.monitor_and_clean(&Shutdown::default()).is_err());
+
+        // Nothing ran, so nothing counts as a purge
+        assert!(!cleaner.clean_if_needed().unwrap());
+        assert_eq!(cleaner.stats().purges, 0);
+
+        // Priority 0 pages alone exceed the threshold, but they can't be purged on their own
+        let mut cleaner =
+            StandbyListCleaner::with_backends(memory.clone(), SimNotification::default())
+                .standby_list_size_threshold("1GB".parse().unwrap())
+                .standby_priorities("0-4".parse().unwrap())
+                .purge_strategy("working-sets,standby".parse().unwrap());
+        cleaner.resolve_thresholds().unwrap();
+        memory.state().lists.page_count_by_priority[0] = 2_000_000_000 / 4096;
+        assert!(cleaner.clean_if_needed().unwrap());
+        assert_eq!(memory.state().purges, [PurgeCommand::PurgeStandbyList]);
+        assert_eq!(cleaner.stats().purges, 1);
+    }
+
+    #[test]
+    fn should_resolve_relative_thresholds_against_total_memory() {
+        let memory = SimMemory::new(0, 0);
+        memory.state().total = 64 << 30;
+        let mut cleaner = StandbyListCleaner::with_backends(memory, SimNotification::default())
+            .standby_list_size_threshold("25%".parse().unwrap())
+            .free_memory_size_threshold("2GiB".parse().unwrap());
+        cleaner.resolve_thresholds().unwrap();
+
+        assert_eq!(cleaner.standbylist_threshold, 16 << 30);
+        assert_eq!(cleaner.freemem_threshold, 2 << 30);
+    }
+
+    #[test]
+    fn should_throttle_notification_waits() {
+        let notification = SimNotification::default();
+        let mut cleaner =
+            StandbyListCleaner::with_backends(SimMemory::new(0, 0), notification.clone())
+                .free_memory_size_threshold("512MB".parse().unwrap());
+        cleaner.resolve_thresholds().unwrap();
+        cleaner.poll_freq = std::time::Duration::from_millis(50);
+
+        cleaner.notification.arm(cleaner.freemem_threshold).unwrap();
+        assert_eq!(notification.state().armed, Some(512_000_000));
+
+        let shutdown = Shutdown::default();
+        let start = std::time::Instant::now();
+        cleaner.wait_on_notification(&shutdown).unwrap();
+        cleaner.wait_on_notification(&shutdown).unwrap();
+        cleaner.wait_on_notification(&shutdown).unwrap();
+        assert!(start.elapsed() >= std::time::Duration::from_millis(100));
+        assert_eq!(notification.state().waits, 3);
+
+        drop(cleaner);
+        assert_eq!(notification.state().armed, None);
+    }
+
+    #[test]
+    fn should_poll_at_throttle_interval() {
+        let memory = SimMemory::new(500_000_000, 3_000_000_000);
+        let mut cleaner = StandbyListCleaner::with_backends(memory.clone(), PollingNotification);
+        cleaner.poll_freq = std::time::Duration::from_millis(50);
+
+        let shutdown = Shutdown::default();
+        let monitor = std::thread::spawn({
+            let shutdown = shutdown.clone();
+            move || {
+                cleaner.monitor_and_clean(&shutdown).unwrap();
+                cleaner
+            }
+        });
+        // Memory keeps running low, so that every poll of the memory lists purges
+        let start = std::time::Instant::now();
+        while start.elapsed() < std::time::Duration::from_millis(300) {
+            memory.set_memory(500_000_000, 3_000_000_000);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        shutdown.request(ShutdownReason::Terminate);
+        let cleaner = monitor.join().unwrap();
+
+        // One purge at startup, then one every 50ms at most
+        let purges = cleaner.stats().purges;
+        assert!((3..=8).contains(&purges), "{} purges", purges);
+    }
+}
@@ -0,0 +1,53 @@
+/// How the standby list cleaner gets woken up to check the memory lists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CleanerMode {
+    /// Waits on the platform memory pressure notification (CMRN on Windows, PSI on Linux)
+    #[default]
+    Notification,
+    /// Samples the memory lists on a fixed interval, without relying on the kernel
+    Poll,
+}
+
+impl CleanerMode {
+    /// Name used on the command line
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Notification => "notification",
+            Self::Poll => "poll",
+        }
+    }
+}
+
+impl std::fmt::Display for CleanerMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("Unknown Standby List cleaning mode: {0:?}, expected notification or poll")]
+pub struct CleanerModeParseError(String);
+
+impl std::str::FromStr for CleanerMode {
+    type Err = CleanerModeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Notification, Self::Poll]
+            .into_iter()
+            .find(|mode| mode.name() == s.trim())
+            .ok_or_else(|| CleanerModeParseError(s.into()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CleanerMode;
+
+    #[test]
+    fn should_parse_modes() {
+        assert_eq!("poll".parse(), Ok(CleanerMode::Poll));
+        assert_eq!("notification".parse(), Ok(CleanerMode::Notification));
+        assert!("cmrn".parse::<CleanerMode>().is_err());
+        assert_eq!(CleanerMode::Poll.to_string(), "poll");
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+
+/// Memory state the purge policy decides on, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemorySnapshot {
+    pub at: Instant,
+    pub free: usize,
+    pub standby: usize,
+}
+
+/// Outcome of a purge policy evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PurgeDecision {
+    Purge,
+    /// Free memory is high enough, or the standby list small enough
+    BelowThresholds,
+    /// The last purge was too recent
+    Cooldown {
+        remaining: Duration,
+    },
+    /// Free memory hasn't recovered above the re-arm threshold since the last purge
+    Disarmed,
+    /// The hourly purge cap is reached
+    RateLimited {
+        remaining: Duration,
+    },
+}
+
+/// Decides whether the standby list should be purged, given successive memory snapshots.
+///
+/// On top of the free memory and standby list thresholds, it enforces a minimum interval between
+/// purges, an optional hourly cap and an optional re-arm threshold: once a purge happened, no other one
+/// happens until free memory went back above it, which avoids purging over and over while free memory
+/// hovers around the threshold.
+///
+/// This is a pure state machine: time is only ever taken from the snapshots.
+#[derive(Debug, Clone)]
+pub struct PurgePolicy {
+    freemem_threshold: usize,
+    standbylist_threshold: usize,
+    min_interval: Duration,
+    rearm_threshold: Option<usize>,
+    max_per_hour: Option<usize>,
+    armed: bool,
+    last_purge: Option<Instant>,
+    /// Purges within the last hour, oldest first
+    recent: VecDeque<Instant>,
+}
+
+impl PurgePolicy {
+    /// Purges whenever free memory is below `freemem_threshold` and the standby list above `standbylist_threshold`
+    pub fn new(freemem_threshold: usize, standbylist_threshold: usize) -> Self {
+        Self {
+            freemem_threshold,
+            standbylist_threshold,
+            min_interval: Duration::ZERO,
+            rearm_threshold: None,
+            max_per_hour: None,
+            armed: true,
+            last_purge: None,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Minimum time between two purges
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Free memory, in bytes, that has to be reached again after a purge before the next one
+    pub fn rearm_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.rearm_threshold = threshold;
+        self
+    }
+
+    /// Maximum number of purges within any hour
+    pub fn max_per_hour(mut self, max: Option<usize>) -> Self {
+        self.max_per_hour = max;
+        self
+    }
+
+    /// Replaces the free memory and standby list thresholds, keeping the purge history
+    pub fn set_thresholds(&mut self, freemem_threshold: usize, standbylist_threshold: usize) {
+        self.freemem_threshold = freemem_threshold;
+        self.standbylist_threshold = standbylist_threshold;
+    }
+
+    pub fn evaluate(&mut self, snapshot: &MemorySnapshot) -> PurgeDecision {
+        if !self.armed && self.rearm_threshold.is_none_or(|t| snapshot.free >= t) {
+            self.armed = true;
+        }
+
+        if snapshot.free >= self.freemem_threshold || snapshot.standby <= self.standbylist_threshold
+        {
+            return PurgeDecision::BelowThresholds;
+        }
+
+        if !self.armed {
+            return PurgeDecision::Disarmed;
+        }
+
+        self.evaluate_limits(snapshot.at)
+    }
+
+    /// Only enforces the minimum interval between purges and the hourly cap,
+    /// for purges that don't depend on memory thresholds such as process launches
+    pub fn evaluate_limits(&mut self, now: Instant) -> PurgeDecision {
+        while matches!(self.recent.front(), Some(&at) if now.saturating_duration_since(at) >= HOUR)
+        {
+            self.recent.pop_front();
+        }
+
+        if let Some(last) = self.last_purge {
+            let elapsed = now.saturating_duration_since(last);
+            if elapsed < self.min_interval {
+                return PurgeDecision::Cooldown {
+                    remaining: self.min_interval - elapsed,
+                };
+            }
+        }
+
+        if let (Some(max), Some(&oldest)) = (self.max_per_hour, self.recent.front()) {
+            if self.recent.len() >= max {
+                return PurgeDecision::RateLimited {
+                    remaining: HOUR - now.saturating_duration_since(oldest),
+                };
+            }
+        }
+
+        PurgeDecision::Purge
+    }
+
+    /// Records a purge that happened at `at`
+    pub fn record_purge(&mut self, at: Instant) {
+        self.last_purge = Some(at);
+        self.recent.push_back(at);
+        if self.rearm_threshold.is_some() {
+            self.armed = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::{MemorySnapshot, PurgeDecision, PurgePolicy};
+
+    const GB: usize = 1_000_000_000;
+
+    struct Clock(Instant);
+
+    impl Clock {
+        fn snapshot(&mut self, elapsed_secs: u64, free: usize, standby: usize) -> MemorySnapshot {
+            self.0 += Duration::from_secs(elapsed_secs);
+            MemorySnapshot {
+                at: self.0,
+                free,
+                standby,
+            }
+        }
+    }
+
+    /// Evaluates the snapshot and records a purge when the policy decides on one
+    fn step(policy: &mut PurgePolicy, snapshot: MemorySnapshot) -> PurgeDecision {
+        let decision = policy.evaluate(&snapshot);
+        if decision == PurgeDecision::Purge {
+            policy.record_purge(snapshot.at);
+        }
+        decision
+    }
+
+    #[test]
+    fn should_purge_on_every_wakeup_by_default() {
+        let mut clock = Clock(Instant::now());
+        let mut policy = PurgePolicy::new(GB, GB);
+
+        let low = clock.snapshot(0, GB / 2, 2 * GB);
+        assert_eq!(step(&mut policy, low), PurgeDecision::Purge);
+        let low = clock.snapshot(0, GB / 2, 2 * GB);
+        assert_eq!(step(&mut policy, low), PurgeDecision::Purge);
+        let high = clock.snapshot(0, 2 * GB, 2 * GB);
+        assert_eq!(step(&mut policy, high), PurgeDecision::BelowThresholds);
+        let small = clock.snapshot(0, GB / 2, GB / 2);
+        assert_eq!(step(&mut policy, small), PurgeDecision::BelowThresholds);
+    }
+
+    #[test]
+    fn should_wait_for_cooldown() {
+        let mut clock = Clock(Instant::now());
+        let mut policy = PurgePolicy::new(GB, GB).min_interval(Duration::from_secs(60));
+
+        assert_eq!(
+            step(&mut policy, clock.snapshot(0, GB / 2, 2 * GB)),
+            PurgeDecision::Purge
+        );
+        assert_eq!(
+            step(&mut policy, clock.snapshot(20, GB / 2, 2 * GB)),
+            PurgeDecision::Cooldown {
+                remaining: Duration::from_secs(40)
+            }
+        );
+        assert_eq!(
+            step(&mut policy, clock.snapshot(40, GB / 2, 2 * GB)),
+            PurgeDecision::Purge
+        );
+    }
+
+    #[test]
+    fn should_only_rearm_once_free_memory_recovered() {
+        let mut clock = Clock(Instant::now());
+        let mut policy = PurgePolicy::new(GB, GB).rearm_threshold(Some(3 * GB / 2));
+
+        assert_eq!(
+            step(&mut policy, clock.snapshot(0, GB / 2, 3 * GB)),
+            PurgeDecision::Purge
+        );
+        // Free memory hovers around the threshold without recovering
+        assert_eq!(
+            step(&mut policy, clock.snapshot(10, GB + 1, 2 * GB)),
+            PurgeDecision::BelowThresholds
+        );
+        assert_eq!(
+            step(&mut policy, clock.snapshot(10, GB - 1, 2 * GB)),
+            PurgeDecision::Disarmed
+        );
+        // Recovered above the re-arm threshold, then low again
+        assert_eq!(
+            step(&mut policy, clock.snapshot(10, 2 * GB, 2 * GB)),
+            PurgeDecision::BelowThresholds
+        );
+        assert_eq!(
+            step(&mut policy, clock.snapshot(10, GB / 2, 2 * GB)),
+            PurgeDecision::Purge
+        );
+    }
+
+    #[test]
+    fn should_cap_purges_per_hour() {
+        let mut clock = Clock(Instant::now());
+        let mut policy = PurgePolicy::new(GB, GB).max_per_hour(Some(2));
+
+        assert_eq!(
+            step(&mut policy, clock.snapshot(0, GB / 2, 2 * GB)),
+            PurgeDecision::Purge
+        );
+        assert_eq!(
+            step(&mut policy, clock.snapshot(600, GB / 2, 2 * GB)),
+            PurgeDecision::Purge
+        );
+        assert_eq!(
+            step(&mut policy, clock.snapshot(600, GB / 2, 2 * GB)),
+            PurgeDecision::RateLimited {
+                remaining: Duration::from_secs(2400)
+            }
+        );
+        // The first purge leaves the window
+        assert_eq!(
+            step(&mut policy, clock.snapshot(2400, GB / 2, 2 * GB)),
+            PurgeDecision::Purge
+        );
+        assert_eq!(
+            step(&mut policy, clock.snapshot(1, GB / 2, 2 * GB)),
+            PurgeDecision::RateLimited {
+                remaining: Duration::from_secs(599)
+            }
+        );
+    }
+}
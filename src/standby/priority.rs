@@ -0,0 +1,108 @@
+use crate::platform::MemoryListInfo;
+
+/// Inclusive range of standby list priorities, from 0 (lowest, i.e. prefetched pages) to 7
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityRange {
+    min: u8,
+    max: u8,
+}
+
+impl Default for PriorityRange {
+    fn default() -> Self {
+        Self { min: 0, max: 7 }
+    }
+}
+
+impl PriorityRange {
+    /// Sums the standby pages within the range
+    pub fn standby_pages(&self, lists: &MemoryListInfo) -> usize {
+        lists.page_count_by_priority[self.min as usize..=self.max as usize]
+            .iter()
+            .sum()
+    }
+}
+
+impl std::fmt::Display for PriorityRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.min == self.max {
+            write!(f, "{}", self.min)
+        } else {
+            write!(f, "{}-{}", self.min, self.max)
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("Invalid priority range: {0:?}, expected priorities between 0 and 7 such as `0-4` or `0`")]
+pub struct PriorityRangeParseError(String);
+
+/// Parses a single priority (`0`) or an inclusive range (`0-4`)
+impl std::str::FromStr for PriorityRange {
+    type Err = PriorityRangeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || PriorityRangeParseError(s.into());
+        let (min, max) = s.split_once('-').unwrap_or((s, s));
+        let min: u8 = min.trim().parse().map_err(|_| err())?;
+        let max: u8 = max.trim().parse().map_err(|_| err())?;
+        if min > max || max > 7 {
+            return Err(err());
+        }
+        Ok(Self { min, max })
+    }
+}
+
+/// Standby list breakdown per priority, for logs
+#[derive(Debug, Clone, Copy)]
+pub struct PriorityBreakdown<'a> {
+    pub lists: &'a MemoryListInfo,
+    pub page_size: usize,
+}
+
+impl std::fmt::Display for PriorityBreakdown<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (priority, pages) in self.lists.page_count_by_priority.iter().enumerate() {
+            if priority > 0 {
+                f.write_str(" / ")?;
+            }
+            write!(f, "p{}: {}MB", priority, pages * self.page_size / 1_000_000)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PriorityBreakdown, PriorityRange};
+    use crate::platform::MemoryListInfo;
+
+    #[test]
+    fn should_parse_and_sum_priority_ranges() {
+        let lists = MemoryListInfo {
+            page_count_by_priority: [1, 2, 3, 4, 5, 6, 7, 8],
+            ..Default::default()
+        };
+
+        let range: PriorityRange = "0-4".parse().unwrap();
+        assert_eq!(range.standby_pages(&lists), 15);
+        assert_eq!(range.to_string(), "0-4");
+
+        let range: PriorityRange = "7".parse().unwrap();
+        assert_eq!(range.standby_pages(&lists), 8);
+        assert_eq!(range.to_string(), "7");
+
+        assert_eq!(PriorityRange::default().standby_pages(&lists), 36);
+        assert!("4-2".parse::<PriorityRange>().is_err());
+        assert!("0-8".parse::<PriorityRange>().is_err());
+        assert!("low".parse::<PriorityRange>().is_err());
+
+        assert_eq!(
+            PriorityBreakdown {
+                lists: &lists,
+                page_size: 1_000_000
+            }
+            .to_string(),
+            "p0: 1MB / p1: 2MB / p2: 3MB / p3: 4MB / p4: 5MB / p5: 6MB / p6: 7MB / p7: 8MB"
+        );
+    }
+}
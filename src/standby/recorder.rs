@@ -0,0 +1,492 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use crate::platform::MemoryListInfo;
+
+/// Columns of the telemetry records, in order. Changing them breaks existing graphs: only ever append.
+///
+/// - `timestamp`: RFC 3339, UTC
+/// - `event`: `poll` for samples taken on each cleaner wakeup, `purge` for samples taken right after a purge
+/// - `page_size`: in bytes; every other `*_pages` column is a page count
+/// - `zero_pages`, `free_pages`, `modified_pages`: zeroed, free and modified lists
+/// - `standby_pages`: whole standby list, then `standby_p0` to `standby_p7` per priority
+/// - `reclaimed_bytes`: free memory gained by the purge, empty (CSV) or `null` (JSONL) for `poll` samples
+pub const RECORD_COLUMNS: [&str; 16] = [
+    "timestamp",
+    "event",
+    "page_size",
+    "zero_pages",
+    "free_pages",
+    "modified_pages",
+    "standby_pages",
+    "standby_p0",
+    "standby_p1",
+    "standby_p2",
+    "standby_p3",
+    "standby_p4",
+    "standby_p5",
+    "standby_p6",
+    "standby_p7",
+    "reclaimed_bytes",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    Csv,
+    JsonLines,
+}
+
+impl RecordFormat {
+    /// `.csv` files get CSV, anything else JSON Lines
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Self::Csv,
+            _ => Self::JsonLines,
+        }
+    }
+}
+
+/// What a telemetry sample was taken for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordEvent {
+    Poll,
+    Purge { reclaimed: usize },
+}
+
+/// Appends timestamped memory list samples to a file, for graphing purposes
+pub struct MemoryRecorder {
+    format: RecordFormat,
+    out: Box<dyn Write + Send>,
+}
+
+impl std::fmt::Debug for MemoryRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryRecorder")
+            .field("format", &self.format)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MemoryRecorder {
+    /// Opens `path` for appending, writing the CSV header if the file is new
+    pub fn open(path: &Path) -> crate::TimersetResult<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        let is_empty = file.metadata()?.len() == 0;
+        Self::with_writer(RecordFormat::from_path(path), file, is_empty)
+    }
+
+    pub fn with_writer<W: Write + Send + 'static>(
+        format: RecordFormat,
+        out: W,
+        write_header: bool,
+    ) -> crate::TimersetResult<Self> {
+        let mut recorder = Self {
+            format,
+            out: Box::new(out),
+        };
+        if write_header && format == RecordFormat::Csv {
+            writeln!(recorder.out, "{}", RECORD_COLUMNS.join(","))?;
+            recorder.out.flush()?;
+        }
+        Ok(recorder)
+    }
+
+    pub fn record(
+        &mut self,
+        timestamp: time::OffsetDateTime,
+        event: RecordEvent,
+        page_size: usize,
+        lists: &MemoryListInfo,
+    ) -> crate::TimersetResult<()> {
+        let record = Record::new(timestamp, event, page_size, lists)?;
+        match self.format {
+            RecordFormat::Csv => writeln!(self.out, "{}", record.to_csv())?,
+            RecordFormat::JsonLines => {
+                serde_json::to_writer(&mut self.out, &record).map_err(|e| eyre::eyre!(e))?;
+                writeln!(self.out)?;
+            }
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// A line of a recording, its fields being [`RECORD_COLUMNS`] in the same order
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct Record {
+    timestamp: String,
+    event: String,
+    page_size: usize,
+    zero_pages: usize,
+    free_pages: usize,
+    modified_pages: usize,
+    standby_pages: usize,
+    standby_p0: usize,
+    standby_p1: usize,
+    standby_p2: usize,
+    standby_p3: usize,
+    standby_p4: usize,
+    standby_p5: usize,
+    standby_p6: usize,
+    standby_p7: usize,
+    reclaimed_bytes: Option<usize>,
+}
+
+impl Record {
+    fn new(
+        timestamp: time::OffsetDateTime,
+        event: RecordEvent,
+        page_size: usize,
+        lists: &MemoryListInfo,
+    ) -> eyre::Result<Self> {
+        let (event, reclaimed_bytes) = match event {
+            RecordEvent::Poll => ("poll", None),
+            RecordEvent::Purge { reclaimed } => ("purge", Some(reclaimed)),
+        };
+        let [standby_p0, standby_p1, standby_p2, standby_p3, standby_p4, standby_p5, standby_p6, standby_p7] =
+            lists.page_count_by_priority;
+        Ok(Self {
+            timestamp: timestamp
+                .format(&time::format_description::well_known::Rfc3339)
+                .map_err(|e| eyre::eyre!(e))?,
+            event: event.into(),
+            page_size,
+            zero_pages: lists.zero_page_count,
+            free_pages: lists.free_page_count,
+            modified_pages: lists.modified_page_count,
+            standby_pages: lists.standby_pages(),
+            standby_p0,
+            standby_p1,
+            standby_p2,
+            standby_p3,
+            standby_p4,
+            standby_p5,
+            standby_p6,
+            standby_p7,
+            reclaimed_bytes,
+        })
+    }
+
+    /// Numeric columns, from `page_size` to `standby_p7`
+    fn pages(&self) -> [usize; 13] {
+        [
+            self.page_size,
+            self.zero_pages,
+            self.free_pages,
+            self.modified_pages,
+            self.standby_pages,
+            self.standby_p0,
+            self.standby_p1,
+            self.standby_p2,
+            self.standby_p3,
+            self.standby_p4,
+            self.standby_p5,
+            self.standby_p6,
+            self.standby_p7,
+        ]
+    }
+
+    fn to_csv(&self) -> String {
+        let mut fields = vec![self.timestamp.clone(), self.event.clone()];
+        fields.extend(self.pages().iter().map(ToString::to_string));
+        fields.push(
+            self.reclaimed_bytes
+                .map(|r| r.to_string())
+                .unwrap_or_default(),
+        );
+        fields.join(",")
+    }
+
+    /// Record from CSV fields keyed by column name
+    fn from_csv(fields: &HashMap<&str, &str>) -> eyre::Result<Self> {
+        let field = |name: &str| {
+            fields
+                .get(name)
+                .copied()
+                .ok_or_else(|| eyre::eyre!("Missing {:?} column", name))
+        };
+        let pages = |name: &str| -> eyre::Result<usize> { Ok(field(name)?.parse()?) };
+
+        Ok(Self {
+            timestamp: field("timestamp")?.into(),
+            event: field("event")?.into(),
+            page_size: pages("page_size")?,
+            zero_pages: pages("zero_pages")?,
+            free_pages: pages("free_pages")?,
+            modified_pages: pages("modified_pages")?,
+            standby_pages: pages("standby_pages")?,
+            standby_p0: pages("standby_p0")?,
+            standby_p1: pages("standby_p1")?,
+            standby_p2: pages("standby_p2")?,
+            standby_p3: pages("standby_p3")?,
+            standby_p4: pages("standby_p4")?,
+            standby_p5: pages("standby_p5")?,
+            standby_p6: pages("standby_p6")?,
+            standby_p7: pages("standby_p7")?,
+            reclaimed_bytes: match field("reclaimed_bytes")? {
+                "" => None,
+                reclaimed => Some(reclaimed.parse()?),
+            },
+        })
+    }
+}
+
+/// A sample read back from a recording
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedSample {
+    pub timestamp: time::OffsetDateTime,
+    pub event: RecordEvent,
+    pub page_size: usize,
+    pub lists: MemoryListInfo,
+}
+
+impl TryFrom<Record> for RecordedSample {
+    type Error = eyre::Report;
+
+    fn try_from(record: Record) -> eyre::Result<Self> {
+        let event = match (record.event.as_str(), record.reclaimed_bytes) {
+            ("poll", _) => RecordEvent::Poll,
+            ("purge", Some(reclaimed)) => RecordEvent::Purge { reclaimed },
+            ("purge", None) => eyre::bail!("Missing reclaimed_bytes for a purge"),
+            (event, _) => eyre::bail!("Unknown event {:?}", event),
+        };
+
+        let lists = MemoryListInfo {
+            zero_page_count: record.zero_pages,
+            free_page_count: record.free_pages,
+            modified_page_count: record.modified_pages,
+            page_count_by_priority: [
+                record.standby_p0,
+                record.standby_p1,
+                record.standby_p2,
+                record.standby_p3,
+                record.standby_p4,
+                record.standby_p5,
+                record.standby_p6,
+                record.standby_p7,
+            ],
+            ..Default::default()
+        };
+
+        Ok(Self {
+            timestamp: time::OffsetDateTime::parse(
+                &record.timestamp,
+                &time::format_description::well_known::Rfc3339,
+            )?,
+            event,
+            page_size: record.page_size,
+            lists,
+        })
+    }
+}
+
+/// Reads back a recording made by [`MemoryRecorder`], in the format matching its extension
+pub fn read_samples(path: &Path) -> crate::TimersetResult<Vec<RecordedSample>> {
+    let contents = std::fs::read_to_string(path)?;
+    let format = RecordFormat::from_path(path);
+    let mut lines = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let header: Vec<&str> = match format {
+        RecordFormat::Csv => match lines.next() {
+            Some((_, header)) => header.split(',').collect(),
+            None => return Ok(vec![]),
+        },
+        RecordFormat::JsonLines => vec![],
+    };
+
+    lines
+        .map(|(i, line)| {
+            let record = match format {
+                RecordFormat::Csv => {
+                    Record::from_csv(&header.iter().copied().zip(line.split(',')).collect())
+                }
+                RecordFormat::JsonLines => serde_json::from_str(line).map_err(eyre::Report::from),
+            };
+            record
+                .and_then(RecordedSample::try_from)
+                .map_err(|e| eyre::eyre!("{:?} line {}: {}", path, i + 1, e).into())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use super::{read_samples, MemoryRecorder, RecordEvent, RecordFormat, RecordedSample};
+    use crate::platform::MemoryListInfo;
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuf {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    fn lists() -> MemoryListInfo {
+        MemoryListInfo {
+            zero_page_count: 1,
+            free_page_count: 2,
+            modified_page_count: 3,
+            page_count_by_priority: [10, 0, 0, 0, 0, 20, 0, 30],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_pick_format_from_extension() {
+        assert_eq!(
+            RecordFormat::from_path("session.CSV".as_ref()),
+            RecordFormat::Csv
+        );
+        assert_eq!(
+            RecordFormat::from_path("session.jsonl".as_ref()),
+            RecordFormat::JsonLines
+        );
+    }
+
+    #[test]
+    fn should_record_csv() {
+        let buf = SharedBuf::default();
+        let mut recorder =
+            MemoryRecorder::with_writer(RecordFormat::Csv, buf.clone(), true).unwrap();
+        recorder
+            .record(
+                time::OffsetDateTime::UNIX_EPOCH,
+                RecordEvent::Poll,
+                4096,
+                &lists(),
+            )
+            .unwrap();
+        recorder
+            .record(
+                time::OffsetDateTime::UNIX_EPOCH,
+                RecordEvent::Purge { reclaimed: 4096 },
+                4096,
+                &lists(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            buf.contents(),
+            "timestamp,event,page_size,zero_pages,free_pages,modified_pages,standby_pages,standby_p0,standby_p1,standby_p2,standby_p3,standby_p4,standby_p5,standby_p6,standby_p7,reclaimed_bytes\n\
+             1970-01-01T00:00:00Z,poll,4096,1,2,3,60,10,0,0,0,0,20,0,30,\n\
+             1970-01-01T00:00:00Z,purge,4096,1,2,3,60,10,0,0,0,0,20,0,30,4096\n"
+        );
+    }
+
+    #[test]
+    fn should_read_back_recordings() {
+        for ext in ["csv", "jsonl"] {
+            let path = std::env::temp_dir().join(format!(
+                "timerset-recording-{}.{}",
+                std::process::id(),
+                ext
+            ));
+            let _ = std::fs::remove_file(&path);
+
+            let mut recorder = MemoryRecorder::open(&path).unwrap();
+            let timestamp = time::OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(90);
+            recorder
+                .record(timestamp, RecordEvent::Poll, 4096, &lists())
+                .unwrap();
+            recorder
+                .record(
+                    timestamp,
+                    RecordEvent::Purge { reclaimed: 8192 },
+                    4096,
+                    &lists(),
+                )
+                .unwrap();
+            drop(recorder);
+
+            let samples = read_samples(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(
+                samples,
+                [
+                    RecordedSample {
+                        timestamp,
+                        event: RecordEvent::Poll,
+                        page_size: 4096,
+                        lists: lists(),
+                    },
+                    RecordedSample {
+                        timestamp,
+                        event: RecordEvent::Purge { reclaimed: 8192 },
+                        page_size: 4096,
+                        lists: lists(),
+                    }
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn should_read_json_lines_written_by_other_tools() {
+        let path = std::env::temp_dir().join(format!(
+            "timerset-recording-reordered-{}.jsonl",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            "{ \"reclaimed_bytes\": 8192, \"event\": \"purge\", \"timestamp\": \"1970-01-01T00:01:30Z\", \
+             \"page_size\": 4096, \"zero_pages\": 1, \"free_pages\": 2, \"modified_pages\": 3, \
+             \"standby_pages\": 60, \"standby_p0\": 10, \"standby_p1\": 0, \"standby_p2\": 0, \
+             \"standby_p3\": 0, \"standby_p4\": 0, \"standby_p5\": 20, \"standby_p6\": 0, \"standby_p7\": 30 }\n",
+        )
+        .unwrap();
+
+        let samples = read_samples(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            samples,
+            [RecordedSample {
+                timestamp: time::OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(90),
+                event: RecordEvent::Purge { reclaimed: 8192 },
+                page_size: 4096,
+                lists: lists(),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_record_json_lines() {
+        let buf = SharedBuf::default();
+        let mut recorder =
+            MemoryRecorder::with_writer(RecordFormat::JsonLines, buf.clone(), true).unwrap();
+        recorder
+            .record(
+                time::OffsetDateTime::UNIX_EPOCH,
+                RecordEvent::Poll,
+                4096,
+                &lists(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            buf.contents(),
+            "{\"timestamp\":\"1970-01-01T00:00:00Z\",\"event\":\"poll\",\"page_size\":4096,\"zero_pages\":1,\"free_pages\":2,\"modified_pages\":3,\"standby_pages\":60,\"standby_p0\":10,\"standby_p1\":0,\"standby_p2\":0,\"standby_p3\":0,\"standby_p4\":0,\"standby_p5\":20,\"standby_p6\":0,\"standby_p7\":30,\"reclaimed_bytes\":null}\n"
+        );
+    }
+}
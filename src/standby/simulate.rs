@@ -0,0 +1,210 @@
+use std::time::{Duration, Instant};
+
+use log::info;
+
+use super::{
+    PurgeDecision, PurgeReason, PurgeRecord, RecordEvent, RecordedSample, StandbyListCleaner,
+};
+use crate::platform::{MemoryBackend, MemoryListInfo, MemoryNotification, PurgeCommand};
+
+/// Memory backend serving recorded samples instead of querying the system.
+/// Purges only change the served sample, as estimated by [`PurgeCommand::simulate`].
+#[derive(Debug, Clone, Default)]
+pub struct ReplayMemory {
+    total: usize,
+    page_size: usize,
+    lists: MemoryListInfo,
+}
+
+impl ReplayMemory {
+    /// `total` is the physical memory of the recorded system, needed to resolve percentage thresholds
+    pub fn new(total: usize) -> Self {
+        Self {
+            total,
+            page_size: 4096,
+            lists: MemoryListInfo::default(),
+        }
+    }
+
+    fn load(&mut self, sample: &RecordedSample) {
+        self.page_size = sample.page_size;
+        self.lists = sample.lists;
+    }
+}
+
+impl MemoryBackend for ReplayMemory {
+    fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    fn total_physical_memory(&mut self) -> crate::TimersetResult<usize> {
+        Ok(self.total)
+    }
+
+    fn acquire_privileges(&mut self) -> crate::TimersetResult<()> {
+        Ok(())
+    }
+
+    fn query_memory_lists(&mut self) -> crate::TimersetResult<MemoryListInfo> {
+        Ok(self.lists)
+    }
+
+    fn purge(&mut self, command: PurgeCommand) -> crate::TimersetResult<()> {
+        command.simulate(&mut self.lists);
+        Ok(())
+    }
+}
+
+impl<N: MemoryNotification> StandbyListCleaner<ReplayMemory, N> {
+    /// Replays recorded wakeups through the same thresholds, throttle, purge policy and strategy as
+    /// `monitor_and_clean`, without touching the system. Returns the purges that would have happened.
+    ///
+    /// Only `poll` samples are replayed: they are what the recording cleaner woke up to.
+    /// Samples following a purge that happened while recording show memory as it was after that purge.
+    pub fn replay(
+        &mut self,
+        samples: &[RecordedSample],
+    ) -> crate::TimersetResult<Vec<PurgeRecord>> {
+        self.resolve_thresholds()?;
+
+        let mut purges = vec![];
+        let first = match samples.first() {
+            Some(sample) => sample.timestamp,
+            None => return Ok(purges),
+        };
+        // The purge policy works with instants: recorded timestamps are mapped onto them
+        let origin = Instant::now();
+        let mut last_wakeup: Option<Instant> = None;
+
+        for sample in samples.iter().filter(|s| s.event == RecordEvent::Poll) {
+            let at = origin + Duration::try_from(sample.timestamp - first).unwrap_or_default();
+            if last_wakeup.is_some_and(|last| at - last < self.poll_freq) {
+                continue;
+            }
+            last_wakeup = Some(at);
+
+            self.memory.load(sample);
+            let lists = self.memory.query_memory_lists()?;
+            let snapshot = self.snapshot(at, &lists);
+            if self.policy.evaluate(&snapshot) != PurgeDecision::Purge {
+                continue;
+            }
+
+            let reason = PurgeReason::Thresholds {
+                free_threshold: self.freemem_threshold,
+                standby_threshold: self.standbylist_threshold,
+            };
+            let Some(mut record) = self.run_purge_strategy(reason, lists)? else {
+                continue;
+            };
+            record.timestamp = sample.timestamp;
+            self.policy.record_purge(at);
+            self.stats.record(record.clone());
+            purges.push(record);
+        }
+
+        Ok(purges)
+    }
+}
+
+/// Logs the purges found by a replay
+pub fn log_replay(samples: &[RecordedSample], purges: &[PurgeRecord]) {
+    if let (Some(first), Some(last)) = (samples.first(), samples.last()) {
+        info!(
+            "Replayed {} samples from {} to {}",
+            samples.len(),
+            first.timestamp,
+            last.timestamp
+        );
+    }
+    for purge in purges {
+        info!("Would have purged: {}", purge);
+    }
+    info!(
+        "{} purges would have reclaimed {}MB in total",
+        purges.len(),
+        purges.iter().map(PurgeRecord::reclaimed).sum::<usize>() / 1_000_000
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::ReplayMemory;
+    use crate::platform::{MemoryListInfo, PollingNotification, PurgeCommand};
+    use crate::standby::{RecordEvent, RecordedSample, StandbyListCleaner};
+
+    fn sample(secs: i64, event: RecordEvent, free_mb: usize, standby_mb: usize) -> RecordedSample {
+        let mut lists = MemoryListInfo {
+            free_page_count: free_mb * 1_000_000 / 4096,
+            ..Default::default()
+        };
+        lists.page_count_by_priority[0] = standby_mb * 1_000_000 / 4096 / 2;
+        lists.page_count_by_priority[5] = standby_mb * 1_000_000 / 4096 / 2;
+        RecordedSample {
+            timestamp: time::OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(secs),
+            event,
+            page_size: 4096,
+            lists,
+        }
+    }
+
+    fn recording() -> Vec<RecordedSample> {
+        vec![
+            sample(0, RecordEvent::Poll, 2000, 3000),
+            sample(10, RecordEvent::Poll, 800, 3000),
+            sample(10, RecordEvent::Purge { reclaimed: 0 }, 800, 3000),
+            sample(20, RecordEvent::Poll, 900, 3000),
+            sample(30, RecordEvent::Poll, 700, 3000),
+            sample(40, RecordEvent::Poll, 600, 500),
+        ]
+    }
+
+    #[test]
+    fn should_replay_recorded_samples() {
+        let mut cleaner =
+            StandbyListCleaner::with_backends(ReplayMemory::new(0), PollingNotification)
+                .free_memory_size_threshold("1GB".parse().unwrap())
+                .standby_list_size_threshold("1GB".parse().unwrap())
+                .poll_interval(0);
+        let purges = cleaner.replay(&recording()).unwrap();
+
+        assert_eq!(purges.len(), 3);
+        assert_eq!(
+            purges[0].timestamp,
+            time::OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(10)
+        );
+        assert_eq!(purges[0].steps, [PurgeCommand::PurgeStandbyList]);
+        assert_eq!(
+            purges[0].reclaimed(),
+            recording()[1].lists.standby_pages() * 4096
+        );
+        assert_eq!(cleaner.stats().purges, 3);
+    }
+
+    #[test]
+    fn should_replay_with_throttle_and_policy() {
+        let mut cleaner = StandbyListCleaner::with_backends(
+            ReplayMemory::new(4_000_000_000),
+            PollingNotification,
+        )
+        .free_memory_size_threshold("25%".parse().unwrap())
+        .standby_list_size_threshold("1GB".parse().unwrap())
+        .purge_strategy("low-standby".parse().unwrap())
+        .purge_cooldown(15)
+        .poll_interval(10);
+        let purges = cleaner.replay(&recording()).unwrap();
+
+        // 10s: purge / 20s: above the threshold / 30s: cooldown is over / 40s: standby list too small
+        assert_eq!(
+            purges
+                .iter()
+                .map(|p| p.timestamp.unix_timestamp())
+                .collect::<Vec<_>>(),
+            [10, 30]
+        );
+        assert_eq!(
+            purges[0].reclaimed(),
+            recording()[1].lists.page_count_by_priority[0] * 4096
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use log::info;
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::platform::PurgeCommand;
+
+/// Number of purges kept in the rolling history by default
+pub const DEFAULT_HISTORY_SIZE: usize = 100;
+
+/// Why a purge was triggered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PurgeReason {
+    /// Free memory went below the threshold while the standby list was above its own
+    Thresholds {
+        free_threshold: usize,
+        standby_threshold: usize,
+    },
+    /// A watched process started
+    ProcessStarted { name: String },
+}
+
+impl std::fmt::Display for PurgeReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Thresholds {
+                free_threshold,
+                standby_threshold,
+            } => write!(
+                f,
+                "free memory < {}MB and standby list > {}MB",
+                free_threshold / 1_000_000,
+                standby_threshold / 1_000_000
+            ),
+            Self::ProcessStarted { name } => write!(f, "{} started", name),
+        }
+    }
+}
+
+/// A single purge, which may be made of several strategy steps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PurgeRecord {
+    pub timestamp: time::OffsetDateTime,
+    pub reason: PurgeReason,
+    /// Steps that were actually issued
+    pub steps: Vec<PurgeCommand>,
+    pub free_before: usize,
+    pub free_after: usize,
+    pub standby_before: usize,
+    pub standby_after: usize,
+    /// Time spent blocked in the purge calls
+    pub blocked: Duration,
+}
+
+impl PurgeRecord {
+    /// Free memory gained by the purge, in bytes
+    pub fn reclaimed(&self) -> usize {
+        self.free_after.saturating_sub(self.free_before)
+    }
+}
+
+impl std::fmt::Display for PurgeRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {}: reclaimed {}MB in {}ms (free: {}MB -> {}MB / standby: {}MB -> {}MB)",
+            self.timestamp,
+            self.reason,
+            self.reclaimed() / 1_000_000,
+            self.blocked.as_millis(),
+            self.free_before / 1_000_000,
+            self.free_after / 1_000_000,
+            self.standby_before / 1_000_000,
+            self.standby_after / 1_000_000,
+        )
+    }
+}
+
+/// Totals over every purge, and a rolling history of the most recent ones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PurgeStats {
+    pub purges: u64,
+    pub total_reclaimed: u64,
+    pub total_blocked: Duration,
+    history: VecDeque<PurgeRecord>,
+    history_size: usize,
+}
+
+impl Default for PurgeStats {
+    fn default() -> Self {
+        Self::with_history_size(DEFAULT_HISTORY_SIZE)
+    }
+}
+
+impl PurgeStats {
+    pub fn with_history_size(history_size: usize) -> Self {
+        Self {
+            purges: 0,
+            total_reclaimed: 0,
+            total_blocked: Duration::ZERO,
+            history: VecDeque::with_capacity(history_size),
+            history_size,
+        }
+    }
+
+    pub fn record(&mut self, record: PurgeRecord) {
+        self.purges += 1;
+        self.total_reclaimed += record.reclaimed() as u64;
+        self.total_blocked += record.blocked;
+
+        if self.history_size == 0 {
+            return;
+        }
+        if self.history.len() == self.history_size {
+            self.history.pop_front();
+        }
+        self.history.push_back(record);
+    }
+
+    /// Most recent purges, oldest first
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &PurgeRecord> {
+        self.history.iter()
+    }
+}
+
+/// Logs the purge totals, then the most recent purges
+pub fn log_stats(stats: &PurgeStats) {
+    info!("{}", stats);
+    for purge in stats.history() {
+        info!("{}", purge);
+    }
+}
+
+impl std::fmt::Display for PurgeStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Standby List cleaner: {} purges / {}MB reclaimed / {}ms blocked in purge calls",
+            self.purges,
+            self.total_reclaimed / 1_000_000,
+            self.total_blocked.as_millis()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{PurgeReason, PurgeRecord, PurgeStats};
+    use crate::platform::PurgeCommand;
+
+    fn record(free_before: usize, free_after: usize) -> PurgeRecord {
+        PurgeRecord {
+            timestamp: time::OffsetDateTime::UNIX_EPOCH,
+            reason: PurgeReason::Thresholds {
+                free_threshold: 1_000_000_000,
+                standby_threshold: 1_000_000_000,
+            },
+            steps: vec![PurgeCommand::PurgeStandbyList],
+            free_before,
+            free_after,
+            standby_before: 3_000_000_000,
+            standby_after: 0,
+            blocked: Duration::from_millis(250),
+        }
+    }
+
+    #[test]
+    fn should_keep_totals_and_rolling_history() {
+        let mut stats = PurgeStats::with_history_size(2);
+        stats.record(record(0, 1_000_000_000));
+        stats.record(record(0, 2_000_000_000));
+        stats.record(record(500_000_000, 3_000_000_000));
+
+        assert_eq!(stats.purges, 3);
+        assert_eq!(stats.total_reclaimed, 5_500_000_000);
+        assert_eq!(stats.total_blocked, Duration::from_millis(750));
+        assert_eq!(
+            stats
+                .history()
+                .map(PurgeRecord::reclaimed)
+                .collect::<Vec<_>>(),
+            [2_000_000_000, 2_500_000_000]
+        );
+        assert_eq!(
+            stats.to_string(),
+            "Standby List cleaner: 3 purges / 5500MB reclaimed / 750ms blocked in purge calls"
+        );
+    }
+}
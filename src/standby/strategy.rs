@@ -0,0 +1,144 @@
+use crate::platform::{MemoryListInfo, PurgeCommand};
+
+impl PurgeCommand {
+    const ALL: [Self; 4] = [
+        Self::EmptyWorkingSets,
+        Self::FlushModifiedList,
+        Self::PurgeLowPriorityStandbyList,
+        Self::PurgeStandbyList,
+    ];
+
+    /// Name used on the command line
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::EmptyWorkingSets => "working-sets",
+            Self::FlushModifiedList => "modified",
+            Self::PurgeLowPriorityStandbyList => "low-standby",
+            Self::PurgeStandbyList => "standby",
+        }
+    }
+
+    /// Expected effect of the command on the memory lists, for simulations.
+    /// Working sets are not part of the memory lists, so emptying them has no visible effect.
+    pub fn simulate(self, lists: &mut MemoryListInfo) {
+        match self {
+            Self::EmptyWorkingSets => {}
+            Self::FlushModifiedList => {
+                lists.page_count_by_priority[5] += lists.modified_page_count;
+                lists.modified_page_count = 0;
+            }
+            Self::PurgeStandbyList => {
+                lists.free_page_count += lists.standby_pages();
+                lists.page_count_by_priority = [0; 8];
+            }
+            Self::PurgeLowPriorityStandbyList => {
+                lists.free_page_count += lists.page_count_by_priority[0];
+                lists.page_count_by_priority[0] = 0;
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for PurgeCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum PurgeStrategyParseError {
+    #[error(
+        "Unknown purge step: {0:?}, expected one of working-sets, modified, low-standby or standby"
+    )]
+    UnknownStep(String),
+    #[error("A purge strategy needs at least one step")]
+    Empty,
+}
+
+impl std::str::FromStr for PurgeCommand {
+    type Err = PurgeStrategyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|command| command.name() == s.trim())
+            .ok_or_else(|| PurgeStrategyParseError::UnknownStep(s.into()))
+    }
+}
+
+/// Ordered list of memory list commands issued when the cleaning conditions are met.
+/// The first step always runs; each following step only runs if free memory is still below the threshold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PurgeStrategy(Vec<PurgeCommand>);
+
+impl Default for PurgeStrategy {
+    fn default() -> Self {
+        Self(vec![PurgeCommand::PurgeStandbyList])
+    }
+}
+
+impl PurgeStrategy {
+    pub fn steps(&self) -> &[PurgeCommand] {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for PurgeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, step) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            step.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a comma-separated list of steps, e.g. `low-standby,standby`
+impl std::str::FromStr for PurgeStrategy {
+    type Err = PurgeStrategyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let steps = s
+            .split(',')
+            .filter(|step| !step.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if steps.is_empty() {
+            return Err(PurgeStrategyParseError::Empty);
+        }
+
+        Ok(Self(steps))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PurgeStrategy, PurgeStrategyParseError};
+    use crate::platform::PurgeCommand;
+
+    #[test]
+    fn should_parse_and_display_strategies() {
+        let strategy: PurgeStrategy = "low-standby, standby".parse().unwrap();
+        assert_eq!(
+            strategy.steps(),
+            [
+                PurgeCommand::PurgeLowPriorityStandbyList,
+                PurgeCommand::PurgeStandbyList
+            ]
+        );
+        assert_eq!(strategy.to_string(), "low-standby,standby");
+        assert_eq!(PurgeStrategy::default().to_string(), "standby");
+
+        assert_eq!(
+            "".parse::<PurgeStrategy>(),
+            Err(PurgeStrategyParseError::Empty)
+        );
+        assert_eq!(
+            "standby,all".parse::<PurgeStrategy>(),
+            Err(PurgeStrategyParseError::UnknownStep("all".into()))
+        );
+    }
+}
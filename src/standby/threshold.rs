@@ -0,0 +1,181 @@
+/// A memory threshold, either absolute or relative to the total physical memory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemoryThreshold {
+    Bytes(u64),
+    /// Percentage of the total physical memory, between 0 and 100
+    Percent(f64),
+}
+
+impl Default for MemoryThreshold {
+    fn default() -> Self {
+        Self::Bytes(1024 * MemoryUnit::MB.bytes())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MemoryUnit {
+    B,
+    KB,
+    KiB,
+    MB,
+    MiB,
+    GB,
+    GiB,
+}
+
+impl MemoryUnit {
+    /// Units tried when displaying a threshold, from the largest
+    const DISPLAY_ORDER: [Self; 7] = [
+        Self::GiB,
+        Self::GB,
+        Self::MiB,
+        Self::MB,
+        Self::KiB,
+        Self::KB,
+        Self::B,
+    ];
+
+    const fn bytes(self) -> u64 {
+        match self {
+            Self::B => 1,
+            Self::KB => 1_000,
+            Self::KiB => 1 << 10,
+            Self::MB => 1_000_000,
+            Self::MiB => 1 << 20,
+            Self::GB => 1_000_000_000,
+            Self::GiB => 1 << 30,
+        }
+    }
+
+    const fn suffix(self) -> &'static str {
+        match self {
+            Self::B => "B",
+            Self::KB => "KB",
+            Self::KiB => "KiB",
+            Self::MB => "MB",
+            Self::MiB => "MiB",
+            Self::GB => "GB",
+            Self::GiB => "GiB",
+        }
+    }
+
+    fn from_suffix(suffix: &str) -> Option<Self> {
+        Self::DISPLAY_ORDER
+            .into_iter()
+            .find(|unit| unit.suffix().eq_ignore_ascii_case(suffix))
+    }
+}
+
+impl MemoryThreshold {
+    /// Resolves the threshold in bytes against the total physical memory
+    pub fn resolve(&self, total_memory: usize) -> usize {
+        match *self {
+            Self::Bytes(bytes) => bytes as usize,
+            Self::Percent(percent) => (total_memory as f64 * percent / 100.0) as usize,
+        }
+    }
+}
+
+impl std::fmt::Display for MemoryThreshold {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Percent(percent) => write!(f, "{}%", percent),
+            Self::Bytes(0) => write!(f, "0B"),
+            Self::Bytes(bytes) => {
+                let unit = MemoryUnit::DISPLAY_ORDER
+                    .into_iter()
+                    .find(|unit| bytes % unit.bytes() == 0)
+                    .unwrap_or(MemoryUnit::B);
+                write!(f, "{}{}", bytes / unit.bytes(), unit.suffix())
+            }
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum MemoryThresholdParseError {
+    #[error("Invalid memory threshold: {0:?}")]
+    InvalidNumber(String),
+    #[error("Unknown memory unit: {0:?}, expected one of %, B, KB, KiB, MB, MiB, GB or GiB")]
+    UnknownUnit(String),
+    #[error("Memory threshold out of range: {0:?}")]
+    OutOfRange(String),
+}
+
+/// Parses a threshold such as `25%`, `512MiB` or `2GB`. Values without a unit are in MB.
+impl std::str::FromStr for MemoryThreshold {
+    type Err = MemoryThresholdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: f64 = number
+            .parse()
+            .map_err(|_| MemoryThresholdParseError::InvalidNumber(s.into()))?;
+
+        match unit.trim() {
+            "%" if (0.0..=100.0).contains(&number) => Ok(Self::Percent(number)),
+            "%" => Err(MemoryThresholdParseError::OutOfRange(s.into())),
+            unit => {
+                let unit = match unit {
+                    "" => MemoryUnit::MB,
+                    unit => MemoryUnit::from_suffix(unit)
+                        .ok_or_else(|| MemoryThresholdParseError::UnknownUnit(unit.into()))?,
+                };
+                let bytes = (number * unit.bytes() as f64).round();
+                if bytes > u64::MAX as f64 {
+                    return Err(MemoryThresholdParseError::OutOfRange(s.into()));
+                }
+                Ok(Self::Bytes(bytes as u64))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MemoryThreshold, MemoryThresholdParseError};
+
+    #[test]
+    fn should_parse_thresholds() {
+        assert_eq!("25%".parse(), Ok(MemoryThreshold::Percent(25.0)));
+        assert_eq!("1024".parse(), Ok(MemoryThreshold::Bytes(1_024_000_000)));
+        assert_eq!("1024MB".parse(), Ok(MemoryThreshold::Bytes(1_024_000_000)));
+        assert_eq!("1024MiB".parse(), Ok(MemoryThreshold::Bytes(1 << 30)));
+        assert_eq!("1.5GB".parse(), Ok(MemoryThreshold::Bytes(1_500_000_000)));
+        assert_eq!("2gib".parse(), Ok(MemoryThreshold::Bytes(2 << 30)));
+        assert_eq!(
+            "120%".parse::<MemoryThreshold>(),
+            Err(MemoryThresholdParseError::OutOfRange("120%".into()))
+        );
+        assert_eq!(
+            "12XB".parse::<MemoryThreshold>(),
+            Err(MemoryThresholdParseError::UnknownUnit("XB".into()))
+        );
+        assert_eq!(
+            "GB".parse::<MemoryThreshold>(),
+            Err(MemoryThresholdParseError::InvalidNumber("GB".into()))
+        );
+    }
+
+    #[test]
+    fn should_display_thresholds_in_largest_exact_unit() {
+        for threshold in ["25%", "1024MB", "1GiB", "1536MiB", "2GB", "1234B"] {
+            assert_eq!(
+                threshold.parse::<MemoryThreshold>().unwrap().to_string(),
+                threshold
+            );
+        }
+        assert_eq!(MemoryThreshold::default().to_string(), "1024MB");
+    }
+
+    #[test]
+    fn should_resolve_against_total_memory() {
+        let total = 16 << 30;
+        assert_eq!(MemoryThreshold::Percent(25.0).resolve(total), 4 << 30);
+        assert_eq!(MemoryThreshold::Bytes(1 << 30).resolve(total), 1 << 30);
+    }
+}
@@ -0,0 +1,227 @@
+use std::time::{Duration, Instant};
+
+use log::info;
+
+use super::{TimerResolution, TimerResolutionInfo};
+use crate::platform::TimerBackend;
+
+/// Sleep duration measured by the benchmark
+pub const BENCH_SLEEP: Duration = Duration::from_millis(1);
+
+/// Candidates whose p99 overshoot is within this factor of the best one are considered as precise
+const P99_TOLERANCE: f64 = 1.1;
+
+/// Smallest p99 overshoot margin over the best one, so that tiny overshoots aren't told apart by noise
+const P99_MIN_MARGIN: Duration = Duration::from_micros(50);
+
+/// Something that sleeps for a requested duration and reports how long it actually slept
+pub trait SleepProbe {
+    fn sleep(&mut self, duration: Duration) -> Duration;
+}
+
+/// Measures `std::thread::sleep` with the monotonic high-resolution clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadSleep;
+
+impl SleepProbe for ThreadSleep {
+    fn sleep(&mut self, duration: Duration) -> Duration {
+        let start = Instant::now();
+        std::thread::sleep(duration);
+        start.elapsed()
+    }
+}
+
+/// Overshoot statistics of a series of sleeps
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SleepStats {
+    pub samples: usize,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl SleepStats {
+    /// Computes statistics from a series of overshoots
+    pub fn from_overshoots(mut overshoots: Vec<Duration>) -> Self {
+        if overshoots.is_empty() {
+            return Self::default();
+        }
+
+        overshoots.sort_unstable();
+        let samples = overshoots.len();
+        let percentile = |p: usize| overshoots[((samples * p).div_ceil(100)).saturating_sub(1)];
+
+        Self {
+            samples,
+            mean: overshoots.iter().sum::<Duration>() / samples as u32,
+            p50: percentile(50),
+            p99: percentile(99),
+            max: overshoots[samples - 1],
+        }
+    }
+
+    /// Sleeps `samples` times for `duration` and gathers the overshoot statistics
+    pub fn measure<S: SleepProbe>(probe: &mut S, duration: Duration, samples: usize) -> Self {
+        Self::from_overshoots(
+            (0..samples)
+                .map(|_| probe.sleep(duration).saturating_sub(duration))
+                .collect(),
+        )
+    }
+}
+
+/// Benchmark result for a single timer resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchResult {
+    pub requested: TimerResolution,
+    /// Resolution the system actually granted
+    pub applied: TimerResolution,
+    pub stats: SleepStats,
+}
+
+/// Candidate resolutions, from the finest (`max`) to the coarsest (`min`), doubling at each step
+pub fn bench_candidates(max: TimerResolution, min: TimerResolution) -> Vec<TimerResolution> {
+    let mut candidates = vec![];
+    let mut candidate = max;
+    while candidate < min && candidate.as_hns() > 0 {
+        candidates.push(candidate);
+        candidate = TimerResolution::from_hns(candidate.as_hns().saturating_mul(2));
+    }
+    candidates.push(min);
+    candidates
+}
+
+/// Runs the sleep benchmark for each candidate resolution
+pub fn run_bench<T: TimerBackend, S: SleepProbe>(
+    info: &mut TimerResolutionInfo<T>,
+    probe: &mut S,
+    samples: usize,
+) -> crate::TimersetResult<Vec<BenchResult>> {
+    bench_candidates(info.max, info.min)
+        .into_iter()
+        .map(|requested| {
+            info.apply_timer(requested)?;
+            info!(
+                "Benchmarking {} (applied: {}) with {} sleeps of {}ms...",
+                requested,
+                info.cur,
+                samples,
+                BENCH_SLEEP.as_millis()
+            );
+            Ok(BenchResult {
+                requested,
+                applied: info.cur,
+                stats: SleepStats::measure(probe, BENCH_SLEEP, samples),
+            })
+        })
+        .collect()
+}
+
+/// Recommends the coarsest -and therefore cheapest in power and interrupts- resolution
+/// whose p99 overshoot stays within 10% or 50μs, whichever is larger, of the most precise candidate.
+/// Results without samples are ignored.
+/// This is where the jitter-to-cost ratio is the best: any finer resolution costs more without
+/// measurably improving sleep precision.
+pub fn recommend(results: &[BenchResult]) -> Option<&BenchResult> {
+    let measured = || results.iter().filter(|r| r.stats.samples > 0);
+    let best_p99 = measured().map(|r| r.stats.p99).min()?;
+    let tolerance = best_p99
+        .mul_f64(P99_TOLERANCE)
+        .max(best_p99 + P99_MIN_MARGIN);
+    measured()
+        .filter(|r| r.stats.p99 <= tolerance)
+        .max_by_key(|r| r.applied)
+}
+
+/// Logs the benchmark results as a table
+pub fn log_results(results: &[BenchResult]) {
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    info!(
+        "{:>10} | {:>10} | {:>9} | {:>9} | {:>9} | {:>9}",
+        "requested", "applied", "mean", "p50", "p99", "max"
+    );
+    for result in results {
+        info!(
+            "{:>10} | {:>10} | {:>7.3}ms | {:>7.3}ms | {:>7.3}ms | {:>7.3}ms",
+            result.requested.to_string(),
+            result.applied.to_string(),
+            ms(result.stats.mean),
+            ms(result.stats.p50),
+            ms(result.stats.p99),
+            ms(result.stats.max),
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{bench_candidates, recommend, run_bench, SleepProbe, SleepStats};
+    use crate::platform::{sim::SimTimer, TimerBackend as _};
+    use crate::timer::{TimerResolution, TimerResolutionInfo};
+
+    /// Sleeps get rounded up to the next timer tick of the simulated timer
+    struct TickSleep(SimTimer);
+
+    impl SleepProbe for TickSleep {
+        fn sleep(&mut self, duration: Duration) -> Duration {
+            let tick = self.0.query_resolution().unwrap().cur.as_duration();
+            let ticks = duration.as_nanos().div_ceil(tick.as_nanos()).max(1);
+            tick * ticks as u32
+        }
+    }
+
+    #[test]
+    fn should_compute_overshoot_statistics() {
+        let stats =
+            SleepStats::from_overshoots((1..=100).rev().map(Duration::from_micros).collect());
+        assert_eq!(stats.samples, 100);
+        assert_eq!(stats.mean, Duration::from_nanos(50_500));
+        assert_eq!(stats.p50, Duration::from_micros(50));
+        assert_eq!(stats.p99, Duration::from_micros(99));
+        assert_eq!(stats.max, Duration::from_micros(100));
+        assert_eq!(SleepStats::from_overshoots(vec![]), SleepStats::default());
+    }
+
+    #[test]
+    fn should_generate_candidates_between_bounds() {
+        assert_eq!(
+            bench_candidates(
+                TimerResolution::from_hns(5000),
+                TimerResolution::from_hns(156250)
+            ),
+            [5000, 10000, 20000, 40000, 80000, 156250]
+                .into_iter()
+                .map(TimerResolution::from_hns)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_recommend_coarsest_precise_resolution() {
+        let sim = SimTimer::new(156250, 5000, 156250);
+        let mut info = TimerResolutionInfo::fetch_with(sim.clone()).unwrap();
+        let results = run_bench(&mut info, &mut TickSleep(sim), 10).unwrap();
+
+        assert_eq!(results.len(), 6);
+        // 0.5ms and 1ms ticks both wake up right on time for 1ms sleeps
+        assert_eq!(results[0].stats.p99, Duration::ZERO);
+        assert_eq!(results[1].stats.p99, Duration::ZERO);
+        assert_eq!(results[2].stats.p99, Duration::from_millis(1));
+        assert_eq!(
+            recommend(&results).map(|r| r.applied),
+            Some(TimerResolution::from_millis(1))
+        );
+    }
+
+    #[test]
+    fn should_not_recommend_without_samples() {
+        let sim = SimTimer::new(156250, 5000, 156250);
+        let mut info = TimerResolutionInfo::fetch_with(sim.clone()).unwrap();
+        let results = run_bench(&mut info, &mut TickSleep(sim), 0).unwrap();
+        assert!(results.iter().all(|r| r.stats.samples == 0));
+        assert_eq!(recommend(&results), None);
+    }
+}
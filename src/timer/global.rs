@@ -0,0 +1,88 @@
+use crate::platform::{RegistryBackend, RegistryJournal, KERNEL_KEY};
+
+/// Registry value making timer resolution requests of background processes affect the whole system again
+pub const GLOBAL_TIMER_REQUESTS_VALUE: &str = "GlobalTimerResolutionRequests";
+
+/// First Windows build (Windows Server 2022, Windows 11 being 22000) where timer resolution requests
+/// of background processes are ignored unless `GlobalTimerResolutionRequests` is set
+pub const GLOBAL_TIMER_REQUESTS_BUILD: u32 = 20348;
+
+/// Whether timer resolution requests of a background process -such as the installed startup task- apply system-wide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlobalTimerRequests {
+    /// Requests always apply system-wide on this OS build, or on this platform
+    Always,
+    /// `GlobalTimerResolutionRequests` is set
+    Enabled,
+    /// `GlobalTimerResolutionRequests` isn't set: requests of background processes are ignored
+    Disabled,
+}
+
+impl std::fmt::Display for GlobalTimerRequests {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Always => write!(f, "not needed on this system"),
+            Self::Enabled => write!(f, "enabled"),
+            Self::Disabled => write!(
+                f,
+                "disabled, timer requests from background processes are ignored"
+            ),
+        }
+    }
+}
+
+impl GlobalTimerRequests {
+    /// Checks the registry value, on builds where it matters. `build` is the Windows build number, if any.
+    pub fn check<R: RegistryBackend>(
+        registry: &R,
+        build: Option<u32>,
+    ) -> crate::TimersetResult<Self> {
+        match build {
+            Some(build) if build >= GLOBAL_TIMER_REQUESTS_BUILD => {
+                match registry.get_u32(KERNEL_KEY, GLOBAL_TIMER_REQUESTS_VALUE)? {
+                    Some(value) if value != 0 => Ok(Self::Enabled),
+                    _ => Ok(Self::Disabled),
+                }
+            }
+            _ => Ok(Self::Always),
+        }
+    }
+
+    /// Sets `GlobalTimerResolutionRequests`, journaling its previous value so that it can be reverted.
+    /// Takes effect after a reboot.
+    pub fn enable<R: RegistryBackend>(
+        journal: &mut RegistryJournal<R>,
+    ) -> crate::TimersetResult<()> {
+        journal.set_u32(KERNEL_KEY, GLOBAL_TIMER_REQUESTS_VALUE, 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GlobalTimerRequests, GLOBAL_TIMER_REQUESTS_VALUE};
+    use crate::platform::{sim::SimRegistry, RegistryBackend as _, KERNEL_KEY};
+
+    #[test]
+    fn should_check_registry_value_on_recent_builds() {
+        let mut registry = SimRegistry::default();
+        let check =
+            |registry: &SimRegistry, build| GlobalTimerRequests::check(registry, build).unwrap();
+
+        assert_eq!(check(&registry, None), GlobalTimerRequests::Always);
+        // Windows 10 22H2
+        assert_eq!(check(&registry, Some(19045)), GlobalTimerRequests::Always);
+        // Windows 11 23H2
+        assert_eq!(check(&registry, Some(22631)), GlobalTimerRequests::Disabled);
+
+        registry
+            .set_u32(KERNEL_KEY, GLOBAL_TIMER_REQUESTS_VALUE, 0)
+            .unwrap();
+        assert_eq!(check(&registry, Some(22631)), GlobalTimerRequests::Disabled);
+        registry
+            .set_u32(KERNEL_KEY, GLOBAL_TIMER_REQUESTS_VALUE, 1)
+            .unwrap();
+        assert_eq!(check(&registry, Some(20348)), GlobalTimerRequests::Enabled);
+        assert_eq!(check(&registry, Some(19045)), GlobalTimerRequests::Always);
+    }
+}
@@ -0,0 +1,203 @@
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use super::{TimerResolution, TimerResolutionInfo};
+use crate::platform::{NativeTimer, TimerBackend};
+
+/// Requests of the guards sharing a timer, which holds the finest of them
+#[derive(Debug)]
+struct GuardStack<T: TimerBackend> {
+    info: TimerResolutionInfo<T>,
+    /// Resolution before the first guard applied its request
+    previous: TimerResolution,
+    requests: Vec<(u64, TimerResolution)>,
+    next_id: u64,
+}
+
+impl<T: TimerBackend> GuardStack<T> {
+    fn push(&mut self, value: TimerResolution) -> crate::TimersetResult<(u64, TimerResolution)> {
+        let value = self.info.clamp_timer_value(value);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.requests.push((id, value));
+        if let Err(e) = self.apply() {
+            self.requests.pop();
+            return Err(e);
+        }
+        Ok((id, value))
+    }
+
+    fn remove(&mut self, id: u64) -> crate::TimersetResult<()> {
+        self.requests.retain(|&(request, _)| request != id);
+        self.apply()
+    }
+
+    /// Holds the finest request, or releases the timer once no guard is left
+    fn apply(&mut self) -> crate::TimersetResult<()> {
+        match self.requests.iter().map(|&(_, value)| value).min() {
+            Some(value) if self.info.requested() != Some(value) => self.info.apply_timer(value),
+            Some(_) => Ok(()),
+            None => self.info.release_timer(),
+        }
+    }
+}
+
+/// Guards on the native timer all share the same stack, wherever they were created in the process
+static NATIVE_STACK: Mutex<Weak<Mutex<GuardStack<NativeTimer>>>> = Mutex::new(Weak::new());
+
+/// Holds a timer resolution request for as long as it lives, e.g. for the duration of a scope:
+///
+/// ```no_run
+/// use timerset::timer::{TimerResolution, TimerResolutionGuard};
+///
+/// let guard = TimerResolutionGuard::new(TimerResolution::from_millis(1))?;
+/// println!("Timer resolution is now {}", guard.effective());
+/// // The request is released once `guard` goes out of scope
+/// # Ok::<(), timerset::TimersetError>(())
+/// ```
+///
+/// The requested value is clamped between the bounds of allowed timer values.
+/// Guards can be nested: the finest request among the living guards is held, and dropping a guard
+/// goes back to the finest remaining one. The request is released when the last guard gets dropped.
+#[derive(Debug)]
+pub struct TimerResolutionGuard<T: TimerBackend = NativeTimer> {
+    stack: Arc<Mutex<GuardStack<T>>>,
+    id: u64,
+    requested: TimerResolution,
+}
+
+impl TimerResolutionGuard {
+    /// Requests `value` on the native timer, nesting within the other guards of the process
+    pub fn new(value: TimerResolution) -> crate::TimersetResult<Self> {
+        let mut native = NATIVE_STACK.lock().unwrap_or_else(|e| e.into_inner());
+        let stack = match native.upgrade() {
+            Some(stack) => stack,
+            None => {
+                let stack = Self::new_stack(NativeTimer::default())?;
+                *native = Arc::downgrade(&stack);
+                stack
+            }
+        };
+        Self::push(stack, value)
+    }
+}
+
+impl<T: TimerBackend> TimerResolutionGuard<T> {
+    /// Requests `value` on the given timer backend
+    pub fn with_backend(backend: T, value: TimerResolution) -> crate::TimersetResult<Self> {
+        Self::push(Self::new_stack(backend)?, value)
+    }
+
+    /// Requests `value` on the same timer as this guard, for as long as the returned guard lives
+    pub fn nest(&self, value: TimerResolution) -> crate::TimersetResult<Self> {
+        Self::push(self.stack.clone(), value)
+    }
+
+    fn new_stack(backend: T) -> crate::TimersetResult<Arc<Mutex<GuardStack<T>>>> {
+        let info = TimerResolutionInfo::fetch_with(backend)?;
+        Ok(Arc::new(Mutex::new(GuardStack {
+            previous: info.cur,
+            info,
+            requests: vec![],
+            next_id: 0,
+        })))
+    }
+
+    fn push(
+        stack: Arc<Mutex<GuardStack<T>>>,
+        value: TimerResolution,
+    ) -> crate::TimersetResult<Self> {
+        let (id, requested) = lock(&stack).push(value)?;
+        Ok(Self {
+            stack,
+            id,
+            requested,
+        })
+    }
+
+    /// Value requested by this guard, once clamped
+    pub fn requested(&self) -> TimerResolution {
+        self.requested
+    }
+
+    /// Current timer resolution, as reported after the last request. It can be finer than the
+    /// requested value when a nested guard or another program requested a finer one.
+    pub fn effective(&self) -> TimerResolution {
+        lock(&self.stack).info.cur
+    }
+
+    /// Timer resolution before the outermost guard applied its request
+    pub fn previous(&self) -> TimerResolution {
+        lock(&self.stack).previous
+    }
+}
+
+impl<T: TimerBackend> Drop for TimerResolutionGuard<T> {
+    fn drop(&mut self) {
+        if let Err(e) = lock(&self.stack).remove(self.id) {
+            log::warn!("Could not withdraw timer request {}: {}", self.requested, e);
+        }
+    }
+}
+
+/// Poisoning is ignored: the stack is only changed through `push` and `remove`, which keep it consistent
+fn lock<T: TimerBackend>(stack: &Mutex<GuardStack<T>>) -> MutexGuard<'_, GuardStack<T>> {
+    stack.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+    use super::TimerResolutionGuard;
+    use crate::platform::sim::SimTimer;
+    use crate::timer::TimerResolution;
+
+    #[test]
+    fn should_hold_request_for_guard_lifetime() {
+        let sim = SimTimer::new(156250, 5000, 156250);
+        let guard =
+            TimerResolutionGuard::with_backend(sim.clone(), TimerResolution::from_micros(100))
+                .unwrap();
+        // Clamped to the finest allowed value
+        assert_eq!(guard.requested(), TimerResolution::from_micros(500));
+        assert_eq!(guard.effective(), TimerResolution::from_micros(500));
+        assert_eq!(guard.previous(), TimerResolution::from_hns(156250));
+        assert_eq!(sim.state().range.cur, TimerResolution::from_micros(500));
+
+        drop(guard);
+        assert_eq!(sim.state().releases, [TimerResolution::from_micros(500)]);
+        assert_eq!(sim.state().range.cur, TimerResolution::from_hns(156250));
+    }
+
+    #[test]
+    fn should_hold_finest_nested_request() {
+        let sim = SimTimer::new(156250, 5000, 156250);
+        let outer =
+            TimerResolutionGuard::with_backend(sim.clone(), TimerResolution::from_millis(1))
+                .unwrap();
+
+        // A coarser nested request changes nothing
+        let coarse = outer.nest(TimerResolution::from_millis(2)).unwrap();
+        assert_eq!(coarse.effective(), TimerResolution::from_millis(1));
+
+        let fine = outer.nest(TimerResolution::from_micros(500)).unwrap();
+        assert_eq!(outer.effective(), TimerResolution::from_micros(500));
+
+        // Dropped out of order: the finest remaining request is held again
+        drop(outer);
+        assert_eq!(fine.effective(), TimerResolution::from_micros(500));
+        drop(fine);
+        assert_eq!(coarse.effective(), TimerResolution::from_millis(2));
+        assert_eq!(coarse.previous(), TimerResolution::from_hns(156250));
+        drop(coarse);
+
+        let state = sim.state();
+        assert_eq!(
+            state.requests,
+            [
+                TimerResolution::from_millis(1),
+                TimerResolution::from_micros(500),
+                TimerResolution::from_millis(2)
+            ]
+        );
+        assert_eq!(state.releases, [TimerResolution::from_millis(2)]);
+    }
+}
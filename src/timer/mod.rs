@@ -0,0 +1,199 @@
+mod global;
+pub use self::global::*;
+
+mod guard;
+pub use self::guard::*;
+
+mod profile;
+pub use self::profile::*;
+
+mod resolution;
+pub use self::resolution::*;
+
+mod watchdog;
+pub use self::watchdog::*;
+
+pub mod bench;
+pub mod probe;
+
+pub use crate::platform::{NativeTimer, TimerBackend, TimerRange};
+
+/// Kernel timer resolutions, along with the request timerset holds, if any.
+/// The request is released when this gets dropped.
+#[derive(Debug)]
+pub struct TimerResolutionInfo<T: TimerBackend = NativeTimer> {
+    pub cur: TimerResolution,
+    pub min: TimerResolution,
+    pub max: TimerResolution,
+    /// Resolution requested through `apply_timer`, until released
+    requested: Option<TimerResolution>,
+    backend: T,
+}
+
+impl<T: TimerBackend> std::fmt::Display for TimerResolutionInfo<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "System Timer Values: min [{}] / max [{}] / cur [{}]",
+            self.min, self.max, self.cur
+        )
+    }
+}
+
+impl<T: TimerBackend> Drop for TimerResolutionInfo<T> {
+    fn drop(&mut self) {
+        if let Err(e) = self.release_timer() {
+            log::warn!("Could not release the timer resolution request: {}", e);
+        }
+    }
+}
+
+impl TimerResolutionInfo {
+    pub fn fetch() -> crate::TimersetResult<Self> {
+        Self::fetch_with(Default::default())
+    }
+}
+
+impl<T: TimerBackend> TimerResolutionInfo<T> {
+    pub fn fetch_with(backend: T) -> crate::TimersetResult<Self> {
+        let range = backend.query_resolution()?;
+        Ok(Self {
+            cur: range.cur,
+            min: range.min,
+            max: range.max,
+            requested: None,
+            backend,
+        })
+    }
+
+    /// Re-queries the timer resolutions from the system
+    pub fn refresh(&mut self) -> crate::TimersetResult<()> {
+        let range = self.backend.query_resolution()?;
+        self.cur = range.cur;
+        self.min = range.min;
+        self.max = range.max;
+        Ok(())
+    }
+
+    pub fn apply_timer(&mut self, value: TimerResolution) -> crate::TimersetResult<()> {
+        let value = self.clamp_timer_value(value);
+        self.cur = self.backend.set_resolution(value)?;
+        self.requested = Some(value);
+        Ok(())
+    }
+
+    /// Withdraws the resolution requested through `apply_timer`, if any
+    pub fn release_timer(&mut self) -> crate::TimersetResult<()> {
+        if let Some(value) = self.requested.take() {
+            self.cur = self.backend.release_resolution(value)?;
+        }
+        Ok(())
+    }
+
+    /// Resolution currently requested through `apply_timer`, if any
+    pub fn requested(&self) -> Option<TimerResolution> {
+        self.requested
+    }
+
+    /// Value requested when none is given: the backend's default if it has one, the finest value otherwise
+    pub fn default_timer_value(&self) -> TimerResolution {
+        T::DEFAULT_RESOLUTION.map_or(self.max, |value| self.clamp_timer_value(value))
+    }
+
+    /// Whether resolutions apply to the whole system, rather than to the calling thread only
+    pub fn system_wide(&self) -> bool {
+        T::SYSTEM_WIDE
+    }
+
+    /// Whether only a handful of resolutions can be granted, which makes probing them worthwhile
+    pub fn discrete_steps(&self) -> bool {
+        T::DISCRETE_STEPS
+    }
+
+    /// Clamps a value between the bounds of allowed timer values.
+    /// Note that the NT Kernel calls the coarsest resolution `min` and the finest one `max`.
+    pub fn clamp_timer_value(&self, value: TimerResolution) -> TimerResolution {
+        if value > self.min {
+            self.min
+        } else if value < self.max {
+            self.max
+        } else {
+            value
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TimerResolution, TimerResolutionInfo};
+    use crate::platform::sim::SimTimer;
+
+    #[test]
+    fn should_clamp_and_apply_timer_values() {
+        let sim = SimTimer::new(156250, 5000, 156250);
+        let mut info = TimerResolutionInfo::fetch_with(sim.clone()).unwrap();
+        assert_eq!(info.min, TimerResolution::from_hns(156250));
+        assert_eq!(info.max, TimerResolution::from_hns(5000));
+        assert_eq!(info.cur, TimerResolution::from_hns(156250));
+
+        info.apply_timer(TimerResolution::from_micros(100)).unwrap();
+        assert_eq!(info.cur, TimerResolution::from_micros(500));
+        info.apply_timer(TimerResolution::from_millis(20)).unwrap();
+        assert_eq!(info.cur, TimerResolution::from_hns(156250));
+        info.apply_timer(TimerResolution::from_millis(1)).unwrap();
+        assert_eq!(info.cur, TimerResolution::from_millis(1));
+
+        assert_eq!(
+            sim.state().requests,
+            vec![
+                TimerResolution::from_hns(5000),
+                TimerResolution::from_hns(156250),
+                TimerResolution::from_hns(10000)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_track_and_release_timer_requests() {
+        let sim = SimTimer::new(156250, 5000, 156250);
+        let mut info = TimerResolutionInfo::fetch_with(sim.clone()).unwrap();
+
+        // Nothing held, nothing to release
+        info.release_timer().unwrap();
+        assert!(sim.state().releases.is_empty());
+
+        info.apply_timer(TimerResolution::from_millis(1)).unwrap();
+        info.apply_timer(TimerResolution::from_micros(500)).unwrap();
+        assert_eq!(info.requested(), Some(TimerResolution::from_micros(500)));
+
+        // Only the latest request is held
+        info.release_timer().unwrap();
+        assert_eq!(info.requested(), None);
+        assert_eq!(info.cur, TimerResolution::from_hns(156250));
+        info.release_timer().unwrap();
+        assert_eq!(sim.state().releases, [TimerResolution::from_micros(500)]);
+
+        info.apply_timer(TimerResolution::from_millis(2)).unwrap();
+        drop(info);
+        assert_eq!(
+            sim.state().releases,
+            [
+                TimerResolution::from_micros(500),
+                TimerResolution::from_millis(2)
+            ]
+        );
+
+        // Dropping without a request doesn't release anything
+        drop(TimerResolutionInfo::fetch_with(sim.clone()).unwrap());
+        assert_eq!(sim.state().releases.len(), 2);
+    }
+
+    #[test]
+    fn should_display_values_in_milliseconds() {
+        let info = TimerResolutionInfo::fetch_with(SimTimer::new(156250, 5000, 10000)).unwrap();
+        assert_eq!(
+            info.to_string(),
+            "System Timer Values: min [15.625ms] / max [0.5ms] / cur [1ms]"
+        );
+    }
+}
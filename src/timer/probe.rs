@@ -0,0 +1,124 @@
+use log::{debug, info};
+
+use super::{TimerResolution, TimerResolutionInfo};
+use crate::platform::TimerBackend;
+
+/// Default interval between two probed values
+pub const PROBE_STEP: TimerResolution = TimerResolution::from_hns(500);
+
+/// Candidate resolutions, from the finest (`max`) to the coarsest (`min`), `step` apart
+pub fn probe_candidates(
+    max: TimerResolution,
+    min: TimerResolution,
+    step: TimerResolution,
+) -> Vec<TimerResolution> {
+    let step = step.as_hns().max(1);
+    let mut candidates: Vec<_> = (max.as_hns()..min.as_hns())
+        .step_by(step as usize)
+        .map(TimerResolution::from_hns)
+        .collect();
+    candidates.push(min);
+    candidates
+}
+
+/// Applies each candidate resolution and gathers the distinct resolutions the system actually granted,
+/// finest first. The timer request is released once done.
+///
+/// Note that other programs holding a finer request than a candidate make it read back as their value.
+pub fn probe_achievable<T: TimerBackend>(
+    info: &mut TimerResolutionInfo<T>,
+    step: TimerResolution,
+) -> crate::TimersetResult<Vec<TimerResolution>> {
+    let mut achievable = vec![];
+    for requested in probe_candidates(info.max, info.min, step) {
+        info.apply_timer(requested)?;
+        debug!("Probed {} (applied: {})", requested, info.cur);
+        if !achievable.contains(&info.cur) {
+            achievable.push(info.cur);
+        }
+    }
+    info.release_timer()?;
+
+    achievable.sort_unstable();
+    Ok(achievable)
+}
+
+/// Finest achievable resolution that is at least as coarse as `value`, so that snapping never
+/// ends up finer than requested. `None` when every achievable resolution is finer.
+pub fn snap_to_achievable(
+    achievable: &[TimerResolution],
+    value: TimerResolution,
+) -> Option<TimerResolution> {
+    achievable
+        .iter()
+        .copied()
+        .filter(|&granted| granted >= value)
+        .min()
+}
+
+/// Logs the achievable resolutions, finest first
+pub fn log_achievable(achievable: &[TimerResolution]) {
+    info!("{} achievable timer values:", achievable.len());
+    for value in achievable {
+        info!("{:>10} ({} hns)", value.to_string(), value.as_hns());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{probe_achievable, probe_candidates, snap_to_achievable};
+    use crate::platform::sim::SimTimer;
+    use crate::timer::{TimerResolution, TimerResolutionInfo};
+
+    fn hns(values: &[u32]) -> Vec<TimerResolution> {
+        values
+            .iter()
+            .copied()
+            .map(TimerResolution::from_hns)
+            .collect()
+    }
+
+    #[test]
+    fn should_step_through_candidates_between_bounds() {
+        assert_eq!(
+            probe_candidates(
+                TimerResolution::from_hns(5000),
+                TimerResolution::from_hns(20000),
+                TimerResolution::from_hns(4000)
+            ),
+            hns(&[5000, 9000, 13000, 17000, 20000])
+        );
+    }
+
+    #[test]
+    fn should_gather_distinct_granted_resolutions() {
+        let sim = SimTimer::new(156250, 5000, 156250);
+        sim.state().achievable = hns(&[5000, 9766, 20000, 156250]);
+        let mut info = TimerResolutionInfo::fetch_with(sim.clone()).unwrap();
+
+        let achievable = probe_achievable(&mut info, TimerResolution::from_hns(500)).unwrap();
+        assert_eq!(achievable, hns(&[5000, 9766, 20000, 156250]));
+        assert_eq!(info.requested(), None);
+        assert_eq!(sim.state().releases.len(), 1);
+    }
+
+    #[test]
+    fn should_snap_to_achievable_value_no_finer_than_requested() {
+        let achievable = hns(&[5000, 9766, 10000, 20000, 156250]);
+        let snap = |value| snap_to_achievable(&achievable, TimerResolution::from_hns(value));
+        assert_eq!(snap(9766), Some(TimerResolution::from_hns(9766)));
+        assert_eq!(snap(9700), Some(TimerResolution::from_hns(9766)));
+        assert_eq!(snap(9850), Some(TimerResolution::from_hns(10000)));
+        assert_eq!(snap(10500), Some(TimerResolution::from_hns(20000)));
+        assert_eq!(snap(1000), Some(TimerResolution::from_hns(5000)));
+        // Another program holding 0.5ms makes every coarser request read back as 0.5ms
+        assert_eq!(
+            snap_to_achievable(&hns(&[5000]), TimerResolution::from_hns(10000)),
+            None
+        );
+        assert_eq!(
+            snap_to_achievable(&[], TimerResolution::from_hns(5000)),
+            None
+        );
+    }
+}
@@ -0,0 +1,399 @@
+use log::{info, warn};
+
+use super::{TimerResolution, TimerResolutionInfo, TimerResolutionParseError};
+use crate::platform::{NativeTimer, PowerState, ProcessSource, TimerBackend};
+use crate::power::PowerWatcher;
+use crate::process::{ProcessPatterns, ProcessPatternsParseError};
+use crate::shutdown::Shutdown;
+
+/// Interval between two checks of the running processes
+const PROFILE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Timer value held while any process matching the patterns runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimerProfile {
+    pub patterns: ProcessPatterns,
+    pub value: TimerResolution,
+}
+
+impl std::fmt::Display for TimerProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.patterns, self.value)
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum TimerProfileParseError {
+    #[error("Invalid timer profile: {0:?}, expected executable names and a timer value such as `game.exe=0.5ms`")]
+    MissingValue(String),
+    #[error(transparent)]
+    Patterns(#[from] ProcessPatternsParseError),
+    #[error(transparent)]
+    Value(#[from] TimerResolutionParseError),
+}
+
+/// Parses executable name patterns and a timer value, e.g. `game.exe,*-Win64-Shipping.exe=0.5ms`
+impl std::str::FromStr for TimerProfile {
+    type Err = TimerProfileParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (patterns, value) = s
+            .rsplit_once('=')
+            .ok_or_else(|| TimerProfileParseError::MissingValue(s.into()))?;
+        Ok(Self {
+            patterns: patterns.parse()?,
+            value: value.parse()?,
+        })
+    }
+}
+
+/// Timer request while running on battery
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatteryTimer {
+    /// Withdraws the timer request
+    Release,
+    Hold(TimerResolution),
+}
+
+impl std::fmt::Display for BatteryTimer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Release => f.write_str("release"),
+            Self::Hold(value) => value.fmt(f),
+        }
+    }
+}
+
+/// Parses `release` or a timer value
+impl std::str::FromStr for BatteryTimer {
+    type Err = TimerResolutionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim() == "release" {
+            return Ok(Self::Release);
+        }
+        s.parse().map(Self::Hold)
+    }
+}
+
+/// Change made to the timer request by the profiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileTransition {
+    Applied(TimerResolution),
+    Released,
+}
+
+impl std::fmt::Display for ProfileTransition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Applied(value) => write!(f, "requested {}", value),
+            Self::Released => f.write_str("released the timer request"),
+        }
+    }
+}
+
+/// Holds the finest timer value among the profiles whose processes are running.
+/// When none of them are, the fallback value is held instead, or the timer request is released entirely.
+/// On battery, the battery timer overrides both.
+pub struct TimerProfiles<T: TimerBackend = NativeTimer> {
+    info: TimerResolutionInfo<T>,
+    profiles: Vec<TimerProfile>,
+    fallback: Option<TimerResolution>,
+    source: Box<dyn ProcessSource + Send>,
+    battery: Option<(BatteryTimer, PowerWatcher)>,
+}
+
+impl<T: TimerBackend + std::fmt::Debug> std::fmt::Debug for TimerProfiles<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimerProfiles")
+            .field("info", &self.info)
+            .field("profiles", &self.profiles)
+            .field("fallback", &self.fallback)
+            .field("battery", &self.battery)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: TimerBackend> TimerProfiles<T> {
+    /// Drives the timer request of `info` from the processes listed by `source`
+    pub fn new(
+        info: TimerResolutionInfo<T>,
+        profiles: Vec<TimerProfile>,
+        source: impl ProcessSource + Send + 'static,
+    ) -> Self {
+        Self {
+            info,
+            profiles,
+            fallback: None,
+            source: Box::new(source),
+            battery: None,
+        }
+    }
+
+    /// Timer value held while no profile applies; the request is released when there is none
+    pub fn fallback(mut self, value: Option<TimerResolution>) -> Self {
+        self.fallback = value;
+        self
+    }
+
+    /// Timer request applying while `power` reports running on battery
+    pub fn on_battery(mut self, timer: BatteryTimer, power: PowerWatcher) -> Self {
+        self.battery = Some((timer, power));
+        self
+    }
+
+    /// Timer value that should be held while the given processes run, clamped to the allowed bounds
+    pub fn wanted(&self, running: &[String], power: PowerState) -> Option<TimerResolution> {
+        if let (PowerState::Battery, Some((timer, _))) = (power, &self.battery) {
+            return match timer {
+                BatteryTimer::Release => None,
+                BatteryTimer::Hold(value) => Some(self.info.clamp_timer_value(*value)),
+            };
+        }
+
+        self.profiles
+            .iter()
+            .filter(|profile| running.iter().any(|name| profile.patterns.matches(name)))
+            .map(|profile| profile.value)
+            .min()
+            .or(self.fallback)
+            .map(|value| self.info.clamp_timer_value(value))
+    }
+
+    /// Reads the power source and lists the running processes once,
+    /// then applies or releases the timer request accordingly.
+    /// Failing to read the power source counts as running on AC power.
+    pub fn check(&mut self) -> crate::TimersetResult<Option<ProfileTransition>> {
+        let power = match self.battery.as_mut() {
+            Some((_, watcher)) => match watcher.poll() {
+                Ok(_) => watcher.state(),
+                Err(e) => {
+                    warn!("Could not read the power source, assuming AC power: {}", e);
+                    PowerState::Ac
+                }
+            },
+            None => PowerState::Ac,
+        };
+        let running = if self.profiles.is_empty() {
+            vec![]
+        } else {
+            self.source.running_processes()?
+        };
+        let wanted = self.wanted(&running, power);
+        if wanted == self.info.requested() {
+            return Ok(None);
+        }
+
+        let transition = match wanted {
+            Some(value) => {
+                self.info.apply_timer(value)?;
+                ProfileTransition::Applied(value)
+            }
+            None => {
+                self.info.release_timer()?;
+                ProfileTransition::Released
+            }
+        };
+        info!(
+            "Timer profiles {}, timer now at {}",
+            transition, self.info.cur
+        );
+        Ok(Some(transition))
+    }
+
+    /// Checks the power source and running processes every second until shutdown.
+    /// Failed checks are logged and retried on the next one.
+    pub fn run(&mut self, shutdown: &Shutdown) {
+        loop {
+            if let Err(e) = self.check() {
+                warn!("Timer profile check failed: {}", e);
+            }
+            if shutdown.wait_timeout(PROFILE_CHECK_INTERVAL).is_some() {
+                break;
+            }
+        }
+    }
+
+    /// Hands the timer back, e.g. to release it on shutdown
+    pub fn into_info(self) -> TimerResolutionInfo<T> {
+        self.info
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        BatteryTimer, ProfileTransition, TimerProfile, TimerProfileParseError, TimerProfiles,
+    };
+    use crate::platform::sim::{SimPower, SimProcesses, SimTimer};
+    use crate::platform::PowerState;
+    use crate::power::PowerWatcher;
+    use crate::timer::{TimerResolution, TimerResolutionInfo};
+
+    #[test]
+    fn should_parse_profiles() {
+        let profile: TimerProfile = "game.exe,*-Win64-Shipping.exe=0.5ms".parse().unwrap();
+        assert!(profile.patterns.matches("Shooter-Win64-Shipping.exe"));
+        assert_eq!(profile.value, TimerResolution::from_micros(500));
+        assert_eq!(profile.to_string(), "game.exe,*-win64-shipping.exe=0.5ms");
+
+        assert_eq!(
+            "game.exe".parse::<TimerProfile>(),
+            Err(TimerProfileParseError::MissingValue("game.exe".into()))
+        );
+        assert!(matches!(
+            "game.exe=fast".parse::<TimerProfile>(),
+            Err(TimerProfileParseError::Value(_))
+        ));
+    }
+
+    #[test]
+    fn should_hold_finest_running_profile_and_release_otherwise() {
+        let sim = SimTimer::new(156250, 5000, 156250);
+        let processes = SimProcesses::default();
+        let info = TimerResolutionInfo::fetch_with(sim.clone()).unwrap();
+        let mut profiles = TimerProfiles::new(
+            info,
+            vec![
+                "game.exe=1ms".parse().unwrap(),
+                "obs64.exe=0.5ms".parse().unwrap(),
+                "daw.exe=0.1ms".parse().unwrap(),
+            ],
+            processes.clone(),
+        );
+
+        processes.set_running(&["explorer.exe"]);
+        assert_eq!(profiles.check().unwrap(), None);
+
+        processes.set_running(&["explorer.exe", "game.exe"]);
+        assert_eq!(
+            profiles.check().unwrap(),
+            Some(ProfileTransition::Applied(TimerResolution::from_millis(1)))
+        );
+        assert_eq!(profiles.check().unwrap(), None);
+
+        // The finest profile wins, clamped to the bounds of the system
+        processes.set_running(&["game.exe", "obs64.exe", "daw.exe"]);
+        assert_eq!(
+            profiles.check().unwrap(),
+            Some(ProfileTransition::Applied(TimerResolution::from_micros(
+                500
+            )))
+        );
+
+        processes.set_running(&["explorer.exe"]);
+        assert_eq!(profiles.check().unwrap(), Some(ProfileTransition::Released));
+
+        let state = sim.state();
+        assert_eq!(
+            state.requests,
+            [
+                TimerResolution::from_millis(1),
+                TimerResolution::from_micros(500)
+            ]
+        );
+        assert_eq!(state.releases, [TimerResolution::from_micros(500)]);
+        assert_eq!(state.range.cur, TimerResolution::from_hns(156250));
+    }
+
+    #[test]
+    fn should_hold_fallback_when_no_profile_applies() {
+        let sim = SimTimer::new(156250, 5000, 156250);
+        let processes = SimProcesses::default();
+        let info = TimerResolutionInfo::fetch_with(sim.clone()).unwrap();
+        let mut profiles = TimerProfiles::new(
+            info,
+            vec!["game.exe=0.5ms".parse().unwrap()],
+            processes.clone(),
+        )
+        .fallback(Some(TimerResolution::from_millis(2)));
+
+        assert_eq!(
+            profiles.check().unwrap(),
+            Some(ProfileTransition::Applied(TimerResolution::from_millis(2)))
+        );
+        processes.set_running(&["game.exe"]);
+        assert_eq!(
+            profiles.check().unwrap(),
+            Some(ProfileTransition::Applied(TimerResolution::from_micros(
+                500
+            )))
+        );
+        processes.set_running(&[]);
+        assert_eq!(
+            profiles.check().unwrap(),
+            Some(ProfileTransition::Applied(TimerResolution::from_millis(2)))
+        );
+        assert!(sim.state().releases.is_empty());
+    }
+
+    #[test]
+    fn should_apply_battery_timer_on_battery() {
+        assert_eq!("release".parse(), Ok(BatteryTimer::Release));
+        assert_eq!(
+            "2ms".parse(),
+            Ok(BatteryTimer::Hold(TimerResolution::from_millis(2)))
+        );
+
+        let sim = SimTimer::new(156250, 5000, 156250);
+        let processes = SimProcesses::default();
+        let power = SimPower::default();
+        let info = TimerResolutionInfo::fetch_with(sim.clone()).unwrap();
+        let mut profiles = TimerProfiles::new(
+            info,
+            vec!["game.exe=0.5ms".parse().unwrap()],
+            processes.clone(),
+        )
+        .fallback(Some(TimerResolution::from_millis(1)))
+        .on_battery(BatteryTimer::Release, PowerWatcher::new(power.clone()));
+
+        processes.set_running(&["game.exe"]);
+        assert_eq!(
+            profiles.check().unwrap(),
+            Some(ProfileTransition::Applied(TimerResolution::from_micros(
+                500
+            )))
+        );
+
+        // Battery wins over running profiles
+        power.set(PowerState::Battery);
+        assert_eq!(profiles.check().unwrap(), Some(ProfileTransition::Released));
+        assert_eq!(profiles.check().unwrap(), None);
+
+        power.set(PowerState::Ac);
+        assert_eq!(
+            profiles.check().unwrap(),
+            Some(ProfileTransition::Applied(TimerResolution::from_micros(
+                500
+            )))
+        );
+        assert_eq!(sim.state().releases, [TimerResolution::from_micros(500)]);
+    }
+
+    #[test]
+    fn should_keep_applying_profiles_when_power_source_fails() {
+        let sim = SimTimer::new(156250, 5000, 156250);
+        let processes = SimProcesses::default();
+        let power = SimPower::default();
+        let info = TimerResolutionInfo::fetch_with(sim).unwrap();
+        let mut profiles = TimerProfiles::new(
+            info,
+            vec!["game.exe=0.5ms".parse().unwrap()],
+            processes.clone(),
+        )
+        .on_battery(BatteryTimer::Release, PowerWatcher::new(power.clone()));
+
+        power.set(PowerState::Battery);
+        processes.set_running(&["game.exe"]);
+        assert_eq!(profiles.check().unwrap(), None);
+
+        // Unreadable power source counts as AC, so that the running profile applies
+        power.fail();
+        assert_eq!(
+            profiles.check().unwrap(),
+            Some(ProfileTransition::Applied(TimerResolution::from_micros(
+                500
+            )))
+        );
+    }
+}
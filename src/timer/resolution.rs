@@ -0,0 +1,171 @@
+/// Units a [`TimerResolution`] can be expressed in
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TimerUnit {
+    /// 100-nanosecond units, as used by the NT Kernel
+    HundredNanos,
+    Micros,
+    Millis,
+}
+
+impl TimerUnit {
+    const fn hns_per_unit(self) -> u32 {
+        match self {
+            Self::HundredNanos => 1,
+            Self::Micros => 10,
+            Self::Millis => 10_000,
+        }
+    }
+
+    const fn suffix(self) -> &'static str {
+        match self {
+            Self::HundredNanos => "hns",
+            Self::Micros => "μs",
+            Self::Millis => "ms",
+        }
+    }
+}
+
+/// A timer resolution, stored in the kernel's native 100-nanosecond units
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TimerResolution(u32);
+
+impl TimerResolution {
+    pub const fn from_hns(hns: u32) -> Self {
+        Self(hns)
+    }
+
+    pub const fn from_micros(us: u32) -> Self {
+        Self(us.saturating_mul(10))
+    }
+
+    pub const fn from_millis(ms: u32) -> Self {
+        Self(ms.saturating_mul(10_000))
+    }
+
+    /// Raw value in 100-nanosecond units
+    pub const fn as_hns(self) -> u32 {
+        self.0
+    }
+
+    pub fn as_duration(self) -> std::time::Duration {
+        std::time::Duration::from_nanos(self.0 as u64 * 100)
+    }
+
+    /// Displays the resolution in the given unit, e.g. `5000hns`, `500μs` or `0.5ms`
+    pub fn display_as(self, unit: TimerUnit) -> TimerResolutionDisplay {
+        TimerResolutionDisplay { value: self, unit }
+    }
+}
+
+/// Displays a resolution in milliseconds, the unit timer resolutions are usually talked about in
+impl std::fmt::Display for TimerResolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.display_as(TimerUnit::Millis).fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TimerResolutionDisplay {
+    value: TimerResolution,
+    unit: TimerUnit,
+}
+
+impl std::fmt::Display for TimerResolutionDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = self.value.0 as f64 / self.unit.hns_per_unit() as f64;
+        write!(f, "{}{}", value, self.unit.suffix())
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum TimerResolutionParseError {
+    #[error("Invalid timer value: {0:?}")]
+    InvalidNumber(String),
+    #[error("Unknown timer unit: {0:?}, expected one of hns, us, μs or ms")]
+    UnknownUnit(String),
+    #[error("Timer value out of range: {0:?}")]
+    OutOfRange(String),
+}
+
+/// Parses a resolution with an optional unit suffix: `0.5ms`, `500us`, `500μs` or `5000hns`.
+/// Values without a unit are in microseconds.
+impl std::str::FromStr for TimerResolution {
+    type Err = TimerResolutionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+
+        let unit = match unit.trim() {
+            "hns" => TimerUnit::HundredNanos,
+            "" | "us" | "μs" => TimerUnit::Micros,
+            "ms" => TimerUnit::Millis,
+            unit => return Err(TimerResolutionParseError::UnknownUnit(unit.into())),
+        };
+
+        let number: f64 = number
+            .parse()
+            .map_err(|_| TimerResolutionParseError::InvalidNumber(s.into()))?;
+
+        let hns = (number * unit.hns_per_unit() as f64).round();
+        if !(1.0..=u32::MAX as f64).contains(&hns) {
+            return Err(TimerResolutionParseError::OutOfRange(s.into()));
+        }
+
+        Ok(Self(hns as u32))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TimerResolution, TimerResolutionParseError, TimerUnit};
+
+    #[test]
+    fn should_parse_unit_suffixes() {
+        let half_ms = TimerResolution::from_hns(5000);
+        assert_eq!("0.5ms".parse(), Ok(half_ms));
+        assert_eq!("500us".parse(), Ok(half_ms));
+        assert_eq!("500μs".parse(), Ok(half_ms));
+        assert_eq!("5000hns".parse(), Ok(half_ms));
+        assert_eq!("500".parse(), Ok(half_ms));
+        assert_eq!("15.625ms".parse(), Ok(TimerResolution::from_hns(156250)));
+    }
+
+    #[test]
+    fn should_reject_invalid_values() {
+        assert_eq!(
+            "1s".parse::<TimerResolution>(),
+            Err(TimerResolutionParseError::UnknownUnit("s".into()))
+        );
+        assert_eq!(
+            "ms".parse::<TimerResolution>(),
+            Err(TimerResolutionParseError::InvalidNumber("ms".into()))
+        );
+        assert_eq!(
+            "0hns".parse::<TimerResolution>(),
+            Err(TimerResolutionParseError::OutOfRange("0hns".into()))
+        );
+    }
+
+    #[test]
+    fn should_display_in_every_unit() {
+        let res = TimerResolution::from_hns(5000);
+        assert_eq!(res.to_string(), "0.5ms");
+        assert_eq!(res.display_as(TimerUnit::Micros).to_string(), "500μs");
+        assert_eq!(
+            res.display_as(TimerUnit::HundredNanos).to_string(),
+            "5000hns"
+        );
+        assert_eq!(
+            TimerResolution::from_hns(156250)
+                .display_as(TimerUnit::Millis)
+                .to_string(),
+            "15.625ms"
+        );
+        assert_eq!(TimerResolution::from_micros(500), res);
+        assert_eq!(TimerResolution::from_millis(1).as_hns(), 10_000);
+    }
+}
@@ -0,0 +1,203 @@
+use log::{debug, info, warn};
+
+use super::{TimerResolution, TimerResolutionInfo};
+use crate::platform::{NativeTimer, TimerBackend};
+use crate::shutdown::Shutdown;
+
+/// Counters kept by the [`TimerWatchdog`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WatchdogStats {
+    /// Number of times the timer resolution was queried
+    pub checks: u64,
+    /// Number of times the current resolution was found different from the last check
+    pub changes: u64,
+    /// Number of times the current resolution was found coarser than our request
+    pub drifts: u64,
+    /// Number of successful re-applications of our request
+    pub reapplied: u64,
+    /// Number of failed queries or re-applications
+    pub failures: u64,
+    /// When the last change was observed
+    pub last_change: Option<time::OffsetDateTime>,
+}
+
+impl std::fmt::Display for WatchdogStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Timer watchdog: {} checks / {} changes / {} drifts / {} re-applied / {} failures",
+            self.checks, self.changes, self.drifts, self.reapplied, self.failures
+        )?;
+        if let Some(last_change) = self.last_change {
+            write!(f, " / last change at {}", last_change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of a single watchdog check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogEvent {
+    Unchanged,
+    /// The resolution changed but is still at least as fine as our request
+    Changed {
+        from: TimerResolution,
+        to: TimerResolution,
+    },
+    /// The resolution drifted coarser than our request and got re-applied
+    Corrected {
+        from: TimerResolution,
+        to: TimerResolution,
+    },
+}
+
+/// Periodically re-queries the timer resolution and re-applies the requested value when
+/// something else (another program, a power policy...) made it coarser.
+#[derive(Debug)]
+pub struct TimerWatchdog<T: TimerBackend = NativeTimer> {
+    info: TimerResolutionInfo<T>,
+    requested: TimerResolution,
+    interval: std::time::Duration,
+    last_seen: TimerResolution,
+    stats: WatchdogStats,
+}
+
+impl<T: TimerBackend> TimerWatchdog<T> {
+    /// Watches over `info`, which is expected to already hold the `requested` resolution
+    pub fn new(info: TimerResolutionInfo<T>, requested: TimerResolution) -> Self {
+        Self {
+            last_seen: info.cur,
+            info,
+            requested,
+            interval: std::time::Duration::from_secs(5),
+            stats: WatchdogStats::default(),
+        }
+    }
+
+    /// Configurable check interval in seconds, at least 1
+    pub fn interval(mut self, interval: u64) -> Self {
+        self.interval = std::time::Duration::from_secs(interval.max(1));
+        self
+    }
+
+    pub fn stats(&self) -> &WatchdogStats {
+        &self.stats
+    }
+
+    /// Runs a single check: re-queries the current resolution and corrects it if needed
+    pub fn check(&mut self) -> crate::TimersetResult<WatchdogEvent> {
+        self.stats.checks += 1;
+        if let Err(e) = self.info.refresh() {
+            self.stats.failures += 1;
+            return Err(e);
+        }
+
+        let from = self.last_seen;
+        let to = self.info.cur;
+        if from == to {
+            debug!("Timer resolution unchanged at {}", to);
+            return Ok(WatchdogEvent::Unchanged);
+        }
+
+        self.stats.changes += 1;
+        self.stats.last_change = Some(time::OffsetDateTime::now_utc());
+        self.last_seen = to;
+        info!("Timer resolution changed from {} to {}", from, to);
+
+        // A finer resolution means that some other program requested a lower timer; ours still holds.
+        if to <= self.info.clamp_timer_value(self.requested) {
+            return Ok(WatchdogEvent::Changed { from, to });
+        }
+
+        self.stats.drifts += 1;
+        warn!(
+            "Timer resolution drifted to {}, re-applying {}",
+            to, self.requested
+        );
+        if let Err(e) = self.info.apply_timer(self.requested) {
+            self.stats.failures += 1;
+            return Err(e);
+        }
+        self.stats.reapplied += 1;
+        self.last_seen = self.info.cur;
+        info!("New timer value set: {}", self.info.cur);
+        info!("{}", self.stats);
+
+        Ok(WatchdogEvent::Corrected {
+            from,
+            to: self.info.cur,
+        })
+    }
+
+    /// Gives back the watched timer, i.e. to release its request once the watchdog stopped
+    pub fn into_info(self) -> TimerResolutionInfo<T> {
+        self.info
+    }
+
+    /// Starts the watchdog loop.
+    /// Note that this is a blocking function that runs until a shutdown is requested; errors are logged and retried on the next check.
+    pub fn run(&mut self, shutdown: &Shutdown) {
+        info!(
+            "Starting timer watchdog, checking every {}s",
+            self.interval.as_secs()
+        );
+        while shutdown.wait_timeout(self.interval).is_none() {
+            if let Err(e) = self.check() {
+                warn!("Timer watchdog check failed: {}", e);
+            }
+            debug!("{}", self.stats());
+        }
+        info!("{}", self.stats());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TimerWatchdog, WatchdogEvent};
+    use crate::platform::sim::SimTimer;
+    use crate::timer::{TimerResolution, TimerResolutionInfo};
+
+    fn hns(value: u32) -> TimerResolution {
+        TimerResolution::from_hns(value)
+    }
+
+    #[test]
+    fn should_detect_and_correct_drift() {
+        let sim = SimTimer::new(156250, 5000, 156250);
+        let mut info = TimerResolutionInfo::fetch_with(sim.clone()).unwrap();
+        info.apply_timer(hns(10000)).unwrap();
+        let mut watchdog = TimerWatchdog::new(info, hns(10000));
+
+        assert_eq!(watchdog.check().unwrap(), WatchdogEvent::Unchanged);
+
+        // Another program requests a finer resolution: nothing to correct
+        sim.state().range.cur = hns(5000);
+        assert_eq!(
+            watchdog.check().unwrap(),
+            WatchdogEvent::Changed {
+                from: hns(10000),
+                to: hns(5000)
+            }
+        );
+
+        // Resolution goes back to the default: our request has to be re-applied
+        sim.state().range.cur = hns(156250);
+        assert_eq!(
+            watchdog.check().unwrap(),
+            WatchdogEvent::Corrected {
+                from: hns(5000),
+                to: hns(10000)
+            }
+        );
+        assert_eq!(sim.state().range.cur, hns(10000));
+        assert_eq!(watchdog.check().unwrap(), WatchdogEvent::Unchanged);
+
+        let stats = watchdog.stats();
+        assert_eq!(stats.checks, 4);
+        assert_eq!(stats.changes, 2);
+        assert_eq!(stats.drifts, 1);
+        assert_eq!(stats.reapplied, 1);
+        assert_eq!(stats.failures, 0);
+        assert!(stats.last_change.is_some());
+    }
+}
@@ -1,3 +1,4 @@
 mod start_args;
 pub use self::start_args::*;
+#[cfg(windows)]
 pub mod win_elevated;
@@ -28,25 +28,96 @@ impl StartArgs {
         }
 
         if let Some(timer_value) = args.timer {
-            ret.args.push(format!("--timer {}", timer_value));
+            ret.args.push(format!(
+                "--timer {}",
+                timer_value.display_as(crate::timer::TimerUnit::HundredNanos)
+            ));
+        }
+
+        for profile in &args.timer_profiles {
+            ret.args.push(format!(
+                "--timer-profile \"{}={}\"",
+                profile.patterns,
+                profile
+                    .value
+                    .display_as(crate::timer::TimerUnit::HundredNanos)
+            ));
+        }
+
+        match args.battery_timer {
+            Some(crate::timer::BatteryTimer::Hold(value)) => ret.args.push(format!(
+                "--battery-timer {}",
+                value.display_as(crate::timer::TimerUnit::HundredNanos)
+            )),
+            Some(crate::timer::BatteryTimer::Release) => {
+                ret.args.push("--battery-timer release".to_string())
+            }
+            None => {}
+        }
+
+        if let Some(interval) = args.timer_watchdog {
+            ret.args.push(format!("--watchdog {}", interval));
         }
 
         if args.clean_standby_list {
             ret.args.push("--islc".to_string());
-            if args.clean_standby_list_poll_freq != 10 {
+            if args.clean_standby_list_mode != crate::standby::CleanerMode::default() {
+                ret.args
+                    .push(format!("--islc-mode {}", args.clean_standby_list_mode));
+            }
+            if args.clean_standby_list_poll_freq.get() != 10 {
                 ret.args.push(format!(
                     "--islc-timer {}",
                     args.clean_standby_list_poll_freq
                 ));
             }
-            if args.clear_standby_cached_mem != 1024 {
+            if args.clear_standby_cached_mem != crate::standby::MemoryThreshold::default() {
                 ret.args
                     .push(format!("--cscm {}", args.clear_standby_cached_mem));
             }
-            if args.clear_standby_free_mem != 1024 {
+            if args.clear_standby_free_mem != crate::standby::MemoryThreshold::default() {
                 ret.args
                     .push(format!("--csfm {}", args.clear_standby_free_mem));
             }
+            if args.clean_standby_list_priorities != crate::standby::PriorityRange::default() {
+                ret.args.push(format!(
+                    "--islc-priorities {}",
+                    args.clean_standby_list_priorities
+                ));
+            }
+            if args.clean_standby_list_strategy != crate::standby::PurgeStrategy::default() {
+                ret.args.push(format!(
+                    "--islc-strategy {}",
+                    args.clean_standby_list_strategy
+                ));
+            }
+            if args.clean_standby_list_cooldown != 0 {
+                ret.args.push(format!(
+                    "--islc-cooldown {}",
+                    args.clean_standby_list_cooldown
+                ));
+            }
+            if let Some(rearm) = args.clean_standby_list_rearm {
+                ret.args.push(format!("--islc-rearm {}", rearm));
+            }
+            if let Some(max) = args.clean_standby_list_max_per_hour {
+                ret.args.push(format!("--islc-max-per-hour {}", max));
+            }
+            if args.clean_standby_list_pause_on_battery {
+                ret.args.push("--islc-pause-on-battery".to_string());
+            }
+            if let Some(patterns) = &args.clean_standby_list_processes {
+                ret.args.push(format!("--islc-processes \"{}\"", patterns));
+            }
+            if let Some(threshold) = args.clean_standby_list_process_cached_mem {
+                ret.args.push(format!("--islc-process-cscm {}", threshold));
+            }
+            if let Some(threshold) = args.clean_standby_list_process_free_mem {
+                ret.args.push(format!("--islc-process-csfm {}", threshold));
+            }
+            if let Some(path) = &args.record {
+                ret.args.push(format!("--record \"{}\"", path.display()));
+            }
         }
 
         ret
@@ -0,0 +1,64 @@
+//! Shuts a running timerset down through real signals, in a process of its own:
+//! signals have to be blocked before any thread gets spawned, which the test harness already did.
+#![cfg(target_os = "linux")]
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Starts timerset holding a timer request and waits until it waits for shutdown
+fn start() -> (Child, impl Iterator<Item = String>) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_timerset"))
+        .args(["--timer", "1ms"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap())
+        .lines()
+        .map(Result::unwrap);
+    assert!(
+        lines
+            .by_ref()
+            .any(|line| line.contains("waiting until shutdown")),
+        "timerset exited before waiting for shutdown"
+    );
+    (child, lines)
+}
+
+fn stop_with(signal: libc::c_int, reason: &str) {
+    let (mut child, lines) = start();
+    assert_eq!(unsafe { libc::kill(child.id() as libc::pid_t, signal) }, 0);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if Instant::now() > deadline {
+            child.kill().unwrap();
+            panic!("timerset did not shut down on signal {}", signal);
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    // The process only exits successfully once cleaning up completed
+    assert!(status.success(), "{:?}", status);
+    let lines: Vec<_> = lines.collect();
+    let shutting_down = lines
+        .iter()
+        .position(|line| line.contains(&format!("Shutting down: {}", reason)))
+        .unwrap_or_else(|| panic!("no shutdown reason logged: {:?}", lines));
+    assert!(lines[shutting_down..]
+        .iter()
+        .any(|line| line.contains("Released timer resolution request")));
+}
+
+#[test]
+fn should_shut_down_on_sigterm() {
+    stop_with(libc::SIGTERM, "terminated");
+}
+
+#[test]
+fn should_shut_down_on_sigint() {
+    stop_with(libc::SIGINT, "interrupted");
+}